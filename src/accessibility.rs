@@ -0,0 +1,90 @@
+use sfml::graphics::Color;
+
+// Simulated colour vision deficiencies, so that designers can check a theme is still readable.
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub enum ColorVision {
+    Normal,
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+    Achromatopsia,
+}
+
+impl ColorVision {
+
+    pub const ALL: [ColorVision; 5] = [
+        ColorVision::Normal,
+        ColorVision::Protanopia,
+        ColorVision::Deuteranopia,
+        ColorVision::Tritanopia,
+        ColorVision::Achromatopsia,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ColorVision::Normal        => "Normal",
+            ColorVision::Protanopia    => "Protanopia",
+            ColorVision::Deuteranopia  => "Deuteranopia",
+            ColorVision::Tritanopia    => "Tritanopia",
+            ColorVision::Achromatopsia => "Achromatopsia",
+        }
+    }
+
+    pub fn next(&self) -> ColorVision {
+        let index = Self::ALL.iter().position(|mode| mode == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    // Matrices are from Machado, Oliveira and Fernandes (2009) at full severity.
+    fn matrix(&self) -> [[f32; 3]; 3] {
+        match *self {
+            ColorVision::Normal => [
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [0.0, 0.0, 1.0],
+            ],
+            ColorVision::Protanopia => [
+                [ 0.152286,  1.052583, -0.204868],
+                [ 0.114503,  0.786281,  0.099216],
+                [-0.003882, -0.048116,  1.051998],
+            ],
+            ColorVision::Deuteranopia => [
+                [ 0.367322,  0.860646, -0.227968],
+                [ 0.280085,  0.672501,  0.047413],
+                [-0.011820,  0.042940,  0.968881],
+            ],
+            ColorVision::Tritanopia => [
+                [ 1.255528, -0.076749, -0.178779],
+                [-0.078411,  0.930809,  0.147602],
+                [ 0.004733,  0.691367,  0.303900],
+            ],
+            ColorVision::Achromatopsia => [
+                [0.299, 0.587, 0.114],
+                [0.299, 0.587, 0.114],
+                [0.299, 0.587, 0.114],
+            ],
+        }
+    }
+
+    pub fn simulate(&self, color: Color) -> Color {
+        if *self == ColorVision::Normal {
+            return color;
+        }
+
+        let m = self.matrix();
+        let rgb = [color.r as f32, color.g as f32, color.b as f32];
+
+        let channel = |row: [f32; 3]| -> u8 {
+            let val = row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2];
+            val.max(0.0).min(255.0).round() as u8
+        };
+
+        Color {
+            r: channel(m[0]),
+            g: channel(m[1]),
+            b: channel(m[2]),
+            a: color.a,
+        }
+    }
+
+}
@@ -0,0 +1,80 @@
+// Extra pressure for players who have already finished the levels: a budget of moves, a countdown,
+// or both. Each keeps its own leaderboards in the save file.
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub enum Challenge {
+    Off,
+    Moves,
+    Time,
+    Both,
+}
+
+// The frame rate, which vertical sync holds the game to
+pub const FRAMES_PER_SECOND: u32 = 60;
+
+// Moves allowed on top of the fewest the level can be finished in
+const MOVE_SLACK: u32 = 3;
+
+// The countdown is this long, plus a few seconds for each move of the shortest solution.
+const BASE_SECONDS: u32 = 10;
+const SECONDS_PER_MOVE: u32 = 4;
+
+impl Challenge {
+
+    pub const ALL: [Challenge; 4] = [
+        Challenge::Off,
+        Challenge::Moves,
+        Challenge::Time,
+        Challenge::Both,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Challenge::Off   => "Off",
+            Challenge::Moves => "Moves",
+            Challenge::Time  => "Time",
+            Challenge::Both  => "Moves and time",
+        }
+    }
+
+    // How the challenge is written in the save file
+    pub fn key(&self) -> &'static str {
+        match *self {
+            Challenge::Off   => "off",
+            Challenge::Moves => "moves",
+            Challenge::Time  => "time",
+            Challenge::Both  => "both",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Challenge> {
+        Self::ALL.iter().find(|challenge| challenge.key() == key).cloned()
+    }
+
+    pub fn next(&self) -> Challenge {
+        let index = Self::ALL.iter().position(|challenge| challenge == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    // The most moves allowed on a level which can be finished in `optimum` moves
+    pub fn move_budget(&self, optimum: u32) -> Option<u32> {
+        match *self {
+            Challenge::Moves | Challenge::Both => Some(optimum + MOVE_SLACK),
+            _ => None,
+        }
+    }
+
+    // The countdown in frames for a level which can be finished in `optimum` moves
+    pub fn time_limit(&self, optimum: u32) -> Option<u32> {
+        match *self {
+            Challenge::Time | Challenge::Both => Some((BASE_SECONDS + SECONDS_PER_MOVE * optimum) * FRAMES_PER_SECOND),
+            _ => None,
+        }
+    }
+
+}
+
+// Minutes and seconds, e.g. 1:05
+pub fn format_time(frames: u32) -> String {
+    let seconds = frames / FRAMES_PER_SECOND;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
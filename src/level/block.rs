@@ -0,0 +1,175 @@
+use std::collections::VecDeque;
+
+use level::{Direction, Tile, TileMap};
+use level::player::{self, Action};
+
+use resources::Resources;
+use renderer::Renderer;
+
+use sfml::system::{Vector2, Vector2f, Vector2i};
+use sfml::graphics::{Color, RectangleShape, Shape, Transformable};
+
+// A crate which the player can push. Once pushed it slides like the player does, until it hits
+// something. Blocks stop on snow, deflectors and teleporters, which they stop from working, and
+// fill in any hole they fall down.
+#[derive(Clone)]
+pub struct Block {
+    action_queue: VecDeque<Action>,
+    action_counter: u32,
+    window_pos: Vector2f,
+    scale: f32,
+    map_pos: Vector2i,
+    fell: bool, // Down a hole, which it has filled in
+}
+
+impl Block {
+
+    pub fn new(map_pos: Vector2i) -> Block {
+        Block {
+            action_queue: VecDeque::new(),
+            action_counter: 0,
+            window_pos: Vector2::new(map_pos.x as f32 * 65.0, map_pos.y as f32 * 65.0),
+            scale: 1.0,
+            map_pos,
+            fell: false,
+        }
+    }
+
+    pub fn update(&mut self) {
+
+        player::animate(&mut self.action_queue, &mut self.action_counter, &mut self.window_pos, &mut self.scale);
+
+        if self.action_queue.is_empty() {
+            self.window_pos.x = self.map_pos.x as f32 * 65.0;
+            self.window_pos.y = self.map_pos.y as f32 * 65.0;
+            self.scale = if self.fell { 0.0 } else { 1.0 };
+        }
+
+    }
+
+    pub fn draw(&self, renderer: &mut Renderer, position: Vector2f, res: &Resources) {
+
+        if self.fell && self.is_ready() {
+            return;
+        }
+
+        let theme = res.theme();
+
+        let centre = (position.x + self.window_pos.x + 32.0, position.y + self.window_pos.y + 32.0);
+
+        let mut rect = RectangleShape::with_size(Vector2::new(56.0, 56.0));
+        rect.set_origin((28.0, 28.0));
+        rect.set_position(centre);
+        rect.set_scale((self.scale, self.scale));
+        rect.set_fill_color(&theme.block);
+
+        renderer.rectangle(&rect);
+
+        if res.settings.tile_shapes.get() {
+            // A hollow square, so blocks can't be mistaken for walls
+            rect.set_size((28.0, 28.0));
+            rect.set_origin((14.0, 14.0));
+            rect.set_fill_color(&Color::TRANSPARENT);
+            rect.set_outline_color(&theme.board);
+            rect.set_outline_thickness(3.0);
+
+            renderer.rectangle(&rect);
+        }
+
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.action_queue.is_empty()
+    }
+
+    pub fn finish_actions(&mut self) {
+        self.action_queue.clear();
+        self.action_counter = 0;
+        self.update();
+    }
+
+    pub fn map_pos(&self) -> Vector2i {
+        self.map_pos
+    }
+
+    pub fn fell(&self) -> bool {
+        self.fell
+    }
+
+}
+
+// The block in the way at `pos`, if there is one. Blocks which have filled in a hole are part of
+// the floor now.
+pub fn block_at(blocks: &[Block], pos: Vector2i) -> Option<usize> {
+    blocks.iter().position(|block| !block.fell && block.map_pos == pos)
+}
+
+// Slides blocks[index] in `dir`, starting after `delay` frames so that whatever pushed it can
+// reach it first. Blocks stop against any of the `players`. Returns false if the block couldn't
+// move.
+pub fn push(blocks: &mut [Block], index: usize, dir: Direction, delay: u32, tile_map: &mut TileMap, players: &[Vector2i]) -> bool {
+
+    let unit_vec = dir.get_unit_vec();
+
+    let mut pos = blocks[index].map_pos;
+    let mut count: u32 = 0;
+    let mut fell = false;
+
+    loop {
+        let next = pos + unit_vec;
+
+        if block_at(blocks, next).is_some() || players.contains(&next) {
+            break;
+        }
+
+        match tile_map.get_tile_at(next) {
+            Tile::Wall | Tile::Door(_, false) | Tile::Lock(_) => break,
+
+            Tile::OneWay(allowed_dir) | Tile::Rotating(allowed_dir) if allowed_dir != dir => break,
+
+            Tile::Snow | Tile::Deflector(_) | Tile::Teleporter(..) => {
+                tile_map.break_ice(pos);
+                pos = next;
+                count += 1;
+                break;
+            },
+
+            Tile::Hole => {
+                tile_map.break_ice(pos);
+                tile_map.set_tile_at(next, Tile::Empty);
+                pos = next;
+                count += 1;
+                fell = true;
+                break;
+            },
+
+            _ => {
+                tile_map.break_ice(pos);
+                pos = next;
+                count += 1;
+            },
+        }
+    }
+
+    if count == 0 {
+        return false;
+    }
+
+    let block = &mut blocks[index];
+
+    if delay != 0 {
+        block.action_queue.push_back(Action::Wait(delay));
+    }
+
+    block.action_queue.push_back(Action::Move { dir, steps: count });
+
+    if fell {
+        block.action_queue.push_back(Action::Fall);
+    }
+
+    block.map_pos = pos;
+    block.fell = fell;
+
+    true
+
+}
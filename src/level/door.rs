@@ -0,0 +1,99 @@
+use level::{Tile, TileMap};
+
+use resources::Resources;
+use renderer::Renderer;
+
+use sfml::system::{Vector2, Vector2f, Vector2i};
+use sfml::graphics::{Color, RectangleShape, Shape, Transformable};
+
+// How a door looks. Whether a door is open is part of the TileMap, but doors slide open and shut
+// over a few frames rather than popping, so they're drawn separately from the rest of the board.
+pub struct Door {
+    pos: Vector2i,
+    id: u32,
+    openness: f32, // 0 when shown shut, 1 when shown open
+    delay: u32, // Frames until the door starts moving, while the player gets to the switch
+}
+
+impl Door {
+
+    const FRAMES: f32 = 12.0;
+
+    // Every door in the map, shown as they are now.
+    pub fn find_all(tile_map: &TileMap) -> Vec<Door> {
+        let mut doors = Vec::new();
+
+        for (y, row) in tile_map.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                if let Tile::Door(id, open) = *tile {
+                    doors.push(Door {
+                        pos: Vector2::new(x as i32, y as i32),
+                        id,
+                        openness: if open { 1.0 } else { 0.0 },
+                        delay: 0,
+                    });
+                }
+            }
+        }
+
+        doors
+    }
+
+    // Moves towards however the door is in `tile_map`.
+    pub fn update(&mut self, tile_map: &TileMap) {
+
+        if self.delay > 0 {
+            self.delay -= 1;
+            return;
+        }
+
+        let target = match tile_map.get_tile_at(self.pos) {
+            Tile::Door(_, true) => 1.0,
+            _ => 0.0,
+        };
+
+        if self.openness < target {
+            self.openness = (self.openness + 1.0 / Self::FRAMES).min(target);
+        } else {
+            self.openness = (self.openness - 1.0 / Self::FRAMES).max(target);
+        }
+
+    }
+
+    pub fn draw(&self, renderer: &mut Renderer, position: Vector2f, res: &Resources) {
+        let theme = res.theme();
+
+        let corner = Vector2::new(position.x + self.pos.x as f32 * 65.0, position.y + self.pos.y as f32 * 65.0);
+
+        // Two halves which slide apart into the sides of the tile
+        let half_width = 32.0 * (1.0 - self.openness);
+
+        if half_width > 0.0 {
+            let mut half = RectangleShape::with_size(Vector2::new(half_width, 64.0));
+            half.set_fill_color(&theme.door);
+
+            half.set_position(corner);
+            renderer.rectangle(&half);
+
+            half.set_position((corner.x + 64.0 - half_width, corner.y));
+            renderer.rectangle(&half);
+        }
+
+        // The frame, so that an open door can still be seen
+        let mut frame = RectangleShape::with_size(Vector2::new(60.0, 60.0));
+        frame.set_position((corner.x + 2.0, corner.y + 2.0));
+        frame.set_fill_color(&Color::TRANSPARENT);
+        frame.set_outline_color(&theme.door);
+        frame.set_outline_thickness(2.0);
+
+        renderer.rectangle(&frame);
+    }
+
+}
+
+// Starts the doors linked to switch `id` moving after `delay` frames.
+pub fn press(doors: &mut [Door], id: u32, delay: u32) {
+    for door in doors.iter_mut().filter(|door| door.id == id) {
+        door.delay = delay;
+    }
+}
@@ -0,0 +1,681 @@
+use std::path::{Path, PathBuf};
+
+use level::{BoardLook, Direction, Goal, KeyColor, Level, LevelData, LevelInfo, LevelPack, Orientation, Tile, TileMap};
+use level::block::Block;
+use level::door::Door;
+use level::hud::Hud;
+use level::mesh::BoardMesh;
+use level::player::Player;
+use level::rotating::RotatingArrow;
+use level::solver;
+
+use resources::Resources;
+
+use scene::{Scene, SceneAction, Sceneable};
+
+use typography::{Label, TextStyle};
+
+use renderer::Renderer;
+
+use sfml::window::{Event, Key};
+use sfml::window::mouse::Button;
+use sfml::system::{Vector2, Vector2f, Vector2i};
+use sfml::graphics::{Color, RectangleShape, Shape, Transformable};
+
+use ::{WINDOW_HEIGHT, WINDOW_WIDTH};
+
+// Something which can be painted onto the map
+#[derive(Copy,Clone,PartialEq)]
+enum Brush {
+    Tile(Tile),
+    Block, // On empty ice
+}
+
+// Makes new levels, or changes those in the player's own packs. Tiles are painted with the mouse or
+// the keyboard, and can be played straight away or saved into packs/custom, or back over the level
+// that was opened. The title and goals can be changed here too. The rest of the level's info
+// (author, difficulty, hint and tutorial) is kept as it was, and has to be added to the file by
+// hand.
+pub struct Editor<'a> {
+    tile_map: TileMap,
+    blocks: Vec<Vector2i>,
+    goals: Vec<Goal>,
+    info: LevelInfo,
+    editing_title: bool, // While a new title is being typed
+    brushes: Vec<Brush>, // Each of BRUSHES, as changed with Tab
+    brush: usize,
+    cursor: Vector2i,
+    painting: bool, // While the left mouse button is held down
+    doors: Vec<Door>,
+    arrows: Vec<RotatingArrow>,
+    board: BoardMesh,
+    board_look: Option<BoardLook>, // What the board mesh was built for. None if it needs rebuilding.
+    label_template: Label<'a>, // Copied for each teleporter number and switch letter
+    tile_labels: Vec<Label<'a>>, // Built with the board mesh
+    frame: u32,
+    title_text: Label<'a>, // The size of the map and the brush
+    info_text: Label<'a>, // The level's title and goals
+    status_text: Label<'a>, // What the last command did
+    controls_text: Label<'a>,
+    saved_as: Option<PathBuf>, // Saving again overwrites the same file
+}
+
+impl<'a> Editor<'a> {
+
+    const BRUSHES: [Brush; 18] = [
+        Brush::Tile(Tile::Empty),
+        Brush::Tile(Tile::Wall),
+        Brush::Tile(Tile::Start),
+        Brush::Tile(Tile::Target),
+        Brush::Tile(Tile::Snow),
+        Brush::Tile(Tile::Cracked),
+        Brush::Tile(Tile::Hole),
+        Brush::Tile(Tile::Star),
+        Brush::Block,
+        Brush::Tile(Tile::OneWay(Direction::Up)),
+        Brush::Tile(Tile::Teleporter(0, None)),
+        Brush::Tile(Tile::Switch(0)),
+        Brush::Tile(Tile::Door(0, false)),
+        Brush::Tile(Tile::Key(KeyColor::Red)),
+        Brush::Tile(Tile::Lock(KeyColor::Red)),
+        Brush::Tile(Tile::Deflector(Orientation::Rising)),
+        Brush::Tile(Tile::Conveyor(Direction::Up)),
+        Brush::Tile(Tile::Rotating(Direction::Up)),
+    ];
+
+    const KEY_COLORS: [KeyColor; 4] = [KeyColor::Red, KeyColor::Green, KeyColor::Yellow, KeyColor::Purple];
+
+    // As big as fits in the window around the text
+    const MAX_WIDTH: usize = 12;
+    const MAX_HEIGHT: usize = 6;
+
+    // What G goes through. Any move limit is kept alongside.
+    const OBJECTIVES: [&'static [Goal]; 5] = [
+        &[Goal::ReachTarget],
+        &[Goal::VisitAll],
+        &[Goal::Stars],
+        &[Goal::ReachTarget, Goal::Stars],
+        &[Goal::VisitAll, Goal::Stars],
+    ];
+
+    const CUSTOM_PACK: &'static str = "custom";
+
+    const CONTROLS: &'static str = "Click or Space paint   Right click or C change tile   E teleporter exit   [ ] or scroll brush   Tab change brush\n\
+                                    Arrows move   Shift+arrows resize   P play   H solve   Ctrl+S save   Esc back\n\
+                                    Return title   G goals   M move limit   - + change limit";
+
+    pub fn new(res: &'a Resources) -> Box<Editor<'a>> {
+
+        let mut tiles = vec![vec![Tile::Empty; 8]; 5];
+        tiles[0][0] = Tile::Start;
+        tiles[4][7] = Tile::Target;
+
+        let data = LevelData {
+            name: String::new(),
+            tile_map: TileMap { tiles },
+            blocks: Vec::new(),
+            goals: vec![Goal::ReachTarget],
+            info: LevelInfo::default(),
+        };
+
+        Self::with_data(res, data, None)
+    }
+
+    // Opens a level from one of the player's own packs, to be saved back over its file
+    pub fn open(res: &'a Resources, pack_id: usize, level_id: usize) -> Box<Editor<'a>> {
+        let pack = &res.packs[pack_id];
+        let data = pack.levels[level_id].clone();
+        let path = Path::new(LevelPack::USER_DIR).join(&pack.id).join(format!("{}.level", data.name));
+
+        Self::with_data(res, data, Some(path))
+    }
+
+    fn with_data(res: &'a Resources, data: LevelData, saved_as: Option<PathBuf>) -> Box<Editor<'a>> {
+
+        let tile_map = data.tile_map;
+
+        let mut title_text = Label::new("", TextStyle::Hud, res);
+        title_text.set_position(Hud::POSITION);
+
+        let mut info_text = Label::new("", TextStyle::Caption, res);
+        info_text.set_position((Hud::POSITION.0, Hud::POSITION.1 + 32.0));
+
+        let mut status_text = Label::new("", TextStyle::Caption, res);
+        status_text.set_position((Hud::POSITION.0, Hud::POSITION.1 + 56.0));
+
+        let mut controls_text = Label::new(Self::CONTROLS, TextStyle::Caption, res);
+        let bounds = controls_text.local_bounds();
+        controls_text.set_position(((WINDOW_WIDTH as f32 - bounds.width) / 2.0, WINDOW_HEIGHT as f32 - Hud::POSITION.1 - bounds.top - bounds.height));
+
+        let label_template = Label::new("", TextStyle::Hud, res);
+
+        let mut editor = Box::new(Editor {
+            board: tile_map.build_mesh(res),
+            tile_labels: tile_map.build_labels(tile_map.centred_position(), &label_template),
+            label_template,
+            doors: Door::find_all(&tile_map),
+            arrows: RotatingArrow::find_all(&tile_map),
+            tile_map,
+            blocks: data.blocks,
+            goals: data.goals,
+            info: data.info,
+            editing_title: false,
+            brushes: Self::BRUSHES.to_vec(),
+            brush: 1,
+            cursor: Vector2::new(0, 0),
+            painting: false,
+            board_look: Some(Level::board_look(res)),
+            frame: 0,
+            title_text,
+            info_text,
+            status_text,
+            controls_text,
+            saved_as,
+        });
+
+        editor.update_title();
+        editor.update_info();
+
+        editor
+    }
+
+    fn width(&self) -> usize {
+        self.tile_map.tiles[0].len()
+    }
+
+    fn height(&self) -> usize {
+        self.tile_map.tiles.len()
+    }
+
+    // The level as it's played and saved, named after the file it was last saved to
+    fn level_data(&self) -> LevelData {
+        let name = self.saved_as.as_ref()
+            .and_then(|path| path.file_stem())
+            .map_or("untitled".to_string(), |stem| stem.to_string_lossy().into_owned());
+
+        LevelData {
+            name,
+            tile_map: self.tile_map.clone(),
+            blocks: self.blocks.clone(),
+            goals: self.goals.clone(),
+            info: self.info.clone(),
+        }
+    }
+
+    // Called whenever the map changes
+    fn map_changed(&mut self) {
+        self.doors = Door::find_all(&self.tile_map);
+        self.arrows = RotatingArrow::find_all(&self.tile_map);
+        self.board_look = None;
+        self.update_title();
+    }
+
+    fn update_title(&mut self) {
+        let brush = match self.brushes[self.brush] {
+            Brush::Tile(tile) => Self::describe(tile),
+            Brush::Block => "Block".to_string(),
+        };
+
+        let title = format!("Editor   {} x {}   Brush: {}", self.width(), self.height(), brush);
+        self.title_text.set_string(&title);
+    }
+
+    fn update_info(&mut self) {
+        let title = self.info.title.as_ref().map_or("None", |title| &title[..]);
+        let cursor = if self.editing_title { "_" } else { "" };
+        let goals: Vec<String> = self.goals.iter().map(Goal::key).collect();
+
+        let info = format!("Title: {}{}   Goals: {}", title, cursor, goals.join(", "));
+        self.info_text.set_string(&info);
+    }
+
+    fn set_status(&mut self, status: &str) {
+        self.status_text.set_string(status);
+    }
+
+    // e.g. "One-way, left" or "Teleporter 2". Teleporters are numbered from one, as in game.
+    fn describe(tile: Tile) -> String {
+        let letter = |id: u32| (b'A' + id as u8) as char;
+
+        match tile {
+            Tile::Empty => "Ice".to_string(),
+            Tile::Wall => "Wall".to_string(),
+            Tile::Start => "Start".to_string(),
+            Tile::Target | Tile::LitTarget => "Target".to_string(),
+            Tile::Snow => "Snow".to_string(),
+            Tile::Cracked => "Cracked ice".to_string(),
+            Tile::Hole => "Hole".to_string(),
+            Tile::Star => "Star".to_string(),
+            Tile::OneWay(dir) => format!("One-way, {}", dir.name()),
+            Tile::Teleporter(index, None) => format!("Teleporter {}", index + 1),
+            Tile::Teleporter(index, Some(dir)) => format!("Teleporter {}, exits {}", index + 1, dir.name()),
+            Tile::Switch(id) => format!("Switch {}", letter(id)),
+            Tile::Door(id, _) => format!("Door {}", letter(id)),
+            Tile::Key(color) => format!("Key, {}", color.name()),
+            Tile::Lock(color) => format!("Lock, {}", color.name()),
+            Tile::Deflector(Orientation::Rising) => "Deflector /".to_string(),
+            Tile::Deflector(Orientation::Falling) => "Deflector \\".to_string(),
+            Tile::Conveyor(dir) => format!("Conveyor, {}", dir.name()),
+            Tile::Rotating(dir) => format!("Rotating one-way, {}", dir.name()),
+        }
+    }
+
+    // The next way round of the same kind of tile: the next direction clockwise, teleporter
+    // index, switch and door id, key colour, or the other diagonal.
+    fn next_variant(tile: Tile) -> Tile {
+        match tile {
+            Tile::OneWay(dir) => Tile::OneWay(dir.clockwise()),
+            Tile::Conveyor(dir) => Tile::Conveyor(dir.clockwise()),
+            Tile::Rotating(dir) => Tile::Rotating(dir.clockwise()),
+            Tile::Teleporter(index, exit) => Tile::Teleporter((index + 1) % 10, exit),
+            Tile::Switch(id) => Tile::Switch((id + 1) % 8),
+            Tile::Door(id, _) => Tile::Door((id + 1) % 8, false),
+            Tile::Key(color) => Tile::Key(Self::KEY_COLORS[(color.index() + 1) % 4]),
+            Tile::Lock(color) => Tile::Lock(Self::KEY_COLORS[(color.index() + 1) % 4]),
+            Tile::Deflector(Orientation::Rising) => Tile::Deflector(Orientation::Falling),
+            Tile::Deflector(Orientation::Falling) => Tile::Deflector(Orientation::Rising),
+            _ => tile,
+        }
+    }
+
+    fn paint(&mut self) {
+        let pos = self.cursor;
+
+        match self.brushes[self.brush] {
+            Brush::Tile(tile) => {
+                if self.tile_map.get_tile_at(pos) == tile && !self.blocks.contains(&pos) {
+                    return;
+                }
+
+                self.tile_map.set_tile_at(pos, tile);
+                self.blocks.retain(|&block| block != pos);
+            },
+            Brush::Block => {
+                if self.blocks.contains(&pos) {
+                    return;
+                }
+
+                self.tile_map.set_tile_at(pos, Tile::Empty);
+                self.blocks.push(pos);
+            },
+        }
+
+        self.map_changed();
+    }
+
+    fn change_tile(&mut self) {
+        let tile = self.tile_map.get_tile_at(self.cursor);
+        self.tile_map.set_tile_at(self.cursor, Self::next_variant(tile));
+        self.map_changed();
+
+        let status = format!("Changed to {}", Self::describe(self.tile_map.get_tile_at(self.cursor)));
+        self.set_status(&status);
+    }
+
+    fn change_brush(&mut self) {
+        if let Brush::Tile(tile) = self.brushes[self.brush] {
+            self.brushes[self.brush] = Brush::Tile(Self::next_variant(tile));
+            self.update_title();
+        }
+    }
+
+    fn select_brush(&mut self, forwards: bool) {
+        let count = self.brushes.len();
+        self.brush = if forwards { (self.brush + 1) % count } else { (self.brush + count - 1) % count };
+        self.update_title();
+    }
+
+    // Sends the player off the teleporter under the cursor in no direction, then each direction
+    // clockwise from up.
+    fn change_exit(&mut self) {
+        if let Tile::Teleporter(index, exit) = self.tile_map.get_tile_at(self.cursor) {
+            let exit = match exit {
+                None => Some(Direction::Up),
+                Some(Direction::Left) => None,
+                Some(dir) => Some(dir.clockwise()),
+            };
+
+            self.tile_map.set_tile_at(self.cursor, Tile::Teleporter(index, exit));
+            self.map_changed();
+
+            let status = format!("Changed to {}", Self::describe(self.tile_map.get_tile_at(self.cursor)));
+            self.set_status(&status);
+        }
+    }
+
+    // Adds or removes columns on the right and rows along the bottom. New tiles are empty ice.
+    fn resize(&mut self, columns: i32, rows: i32) {
+        let width = self.width() as i32 + columns;
+        let height = self.height() as i32 + rows;
+
+        // Levels opened from a file may already be bigger, so shrinking is always allowed
+        let too_wide = columns > 0 && width as usize > Self::MAX_WIDTH;
+        let too_high = rows > 0 && height as usize > Self::MAX_HEIGHT;
+
+        if width < 1 || height < 1 || too_wide || too_high {
+            return;
+        }
+
+        for row in self.tile_map.tiles.iter_mut() {
+            row.resize(width as usize, Tile::Empty);
+        }
+
+        self.tile_map.tiles.resize(height as usize, vec![Tile::Empty; width as usize]);
+        self.blocks.retain(|block| block.x < width && block.y < height);
+
+        self.cursor = Vector2::new(self.cursor.x.min(width - 1), self.cursor.y.min(height - 1));
+        self.map_changed();
+    }
+
+    fn move_cursor(&mut self, dir: Direction) {
+        let pos = self.cursor + dir.get_unit_vec();
+
+        if pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < self.width() && (pos.y as usize) < self.height() {
+            self.cursor = pos;
+        }
+    }
+
+    // Typing goes into the title until Return or Escape is pressed. An empty title is left out.
+    // Return starts it too, as the character typed by the key which started it isn't wanted, and
+    // Return's is ignored.
+    fn edit_title(&mut self, editing: bool) {
+        self.editing_title = editing;
+
+        if editing {
+            self.info.title = Some(self.info.title.take().unwrap_or_default());
+            self.set_status("Type the title, then press Return");
+        } else {
+            self.info.title = self.info.title.take()
+                .map(|title| title.trim().to_string())
+                .and_then(|title| if title.is_empty() { None } else { Some(title) });
+            self.set_status("");
+        }
+
+        self.update_info();
+    }
+
+    fn type_title(&mut self, character: char) {
+        let mut title = self.info.title.take().unwrap_or_default();
+
+        match character {
+            '\u{8}' => { title.pop(); },
+            character if !character.is_control() => title.push(character),
+            _ => {},
+        }
+
+        self.info.title = Some(title);
+        self.update_info();
+    }
+
+    fn move_limit(&self) -> Option<u32> {
+        self.goals.iter().filter_map(|&goal| match goal {
+            Goal::Moves(moves) => Some(moves),
+            _ => None,
+        }).next()
+    }
+
+    fn set_move_limit(&mut self, limit: Option<u32>) {
+        self.goals.retain(Goal::is_objective);
+        self.goals.extend(limit.map(Goal::Moves));
+        self.update_info();
+    }
+
+    // Goes on to the next of OBJECTIVES
+    fn change_objectives(&mut self) {
+        let objectives: Vec<Goal> = self.goals.iter().cloned().filter(Goal::is_objective).collect();
+        let index = Self::OBJECTIVES.iter()
+            .position(|&set| set == &objectives[..])
+            .map_or(0, |index| (index + 1) % Self::OBJECTIVES.len());
+
+        let limit = self.move_limit();
+        self.goals = Self::OBJECTIVES[index].to_vec();
+        self.set_move_limit(limit);
+    }
+
+    // Removes the move limit, or adds one of as many moves as the level takes
+    fn toggle_move_limit(&mut self) {
+        if self.move_limit().is_some() {
+            self.set_move_limit(None);
+            return;
+        }
+
+        if let Some(moves) = self.solution() {
+            self.set_move_limit(Some(moves.len() as u32));
+            self.set_status(&format!("Limited to {} moves, as few as the level can be solved in", moves.len()));
+        }
+    }
+
+    fn adjust_move_limit(&mut self, change: i32) {
+        if let Some(limit) = self.move_limit() {
+            let limit = (limit as i32 + change).max(1) as u32;
+            self.set_move_limit(Some(limit));
+        }
+    }
+
+    fn play(&mut self) -> SceneAction {
+        let data = self.level_data();
+
+        match data.check() {
+            Ok(()) => SceneAction::Push(Scene::TestLevel(data)),
+            Err(problem) => {
+                self.set_status(&format!("Can't play, as this level {}", problem));
+                SceneAction::NoChange
+            },
+        }
+    }
+
+    // Runs the solver from the start of the level. None, with the reason shown, if it can't be
+    // solved.
+    fn solution(&mut self) -> Option<Vec<Direction>> {
+        let data = self.level_data();
+
+        if let Err(problem) = data.check() {
+            self.set_status(&format!("Can't solve, as this level {}", problem));
+            return None;
+        }
+
+        let (players, tile_map, blocks) = Level::initial_state(&data);
+        let solution = solver::solve(&players, &tile_map, &blocks, &data.goals);
+
+        if solution.is_none() {
+            self.set_status("No solution");
+        }
+
+        solution
+    }
+
+    // Shows the first few moves of the solution, and makes its length the par
+    fn solve(&mut self) {
+        if let Some(moves) = self.solution() {
+            let mut shown: Vec<String> = moves.iter().take(12).map(|dir| format!("{:?}", dir)).collect();
+
+            if moves.len() > shown.len() {
+                shown.push("...".to_string());
+            }
+
+            self.info.par = Some(moves.len() as u32);
+            self.set_status(&format!("Solvable in {} moves, now the par: {}", moves.len(), shown.join(" ")));
+        }
+    }
+
+    // The first free numbered file in the custom pack
+    fn new_path() -> Option<PathBuf> {
+        let dir = Path::new(LevelPack::USER_DIR).join(Self::CUSTOM_PACK);
+
+        (1..100).map(|number| dir.join(format!("{:02}.level", number))).find(|path| !path.exists())
+    }
+
+    fn save(&mut self) {
+        if let Err(problem) = self.level_data().check() {
+            self.set_status(&format!("Not saved, as this level {}", problem));
+            return;
+        }
+
+        let path = match self.saved_as.clone().or_else(Self::new_path) {
+            Some(path) => path,
+            None => {
+                self.set_status("Not saved, as the custom pack is full");
+                return;
+            },
+        };
+
+        self.saved_as = Some(path.clone());
+
+        let pack = path.parent().and_then(Path::file_name).map_or(String::new(), |pack| pack.to_string_lossy().into_owned());
+
+        let status = match self.level_data().write(&path) {
+            Ok(()) => format!("Saved to {}, which will be in the {} pack next time the game starts", path.display(), pack),
+            Err(error) => format!("Could not save to {}: {}", path.display(), error),
+        };
+
+        self.set_status(&status);
+    }
+
+}
+
+impl<'a> Sceneable for Editor<'a> {
+
+    fn update(&mut self, res: &Resources) -> SceneAction {
+        self.frame = self.frame.wrapping_add(1);
+
+        self.title_text.refresh_color(res);
+        self.info_text.refresh_color(res);
+        self.status_text.refresh_color(res);
+        self.controls_text.refresh_color(res);
+
+        let look = Level::board_look(res);
+
+        if self.board_look != Some(look) {
+            self.board = self.tile_map.build_mesh(res);
+            self.label_template.refresh_color(res);
+            self.tile_labels = self.tile_map.build_labels(self.tile_map.centred_position(), &self.label_template);
+            self.board_look = Some(look);
+        }
+
+        SceneAction::NoChange
+    }
+
+    fn draw(&self, renderer: &mut Renderer, res: &Resources) {
+        renderer.fill(&res.theme().level_background);
+
+        let map_pos = self.tile_map.centred_position();
+
+        self.board.draw(renderer, map_pos, res);
+        self.tile_map.draw_conveyors(renderer, map_pos, self.frame, res);
+
+        for door in self.doors.iter() {
+            door.draw(renderer, map_pos, res);
+        }
+
+        for arrow in self.arrows.iter() {
+            arrow.draw(renderer, map_pos, res);
+        }
+
+        for label in self.tile_labels.iter() {
+            label.draw(renderer);
+        }
+
+        self.tile_map.draw_teleporter_links(renderer, map_pos, self.cursor, res);
+
+        for &pos in self.blocks.iter() {
+            Block::new(pos).draw(renderer, map_pos, res);
+        }
+
+        for pos in self.tile_map.start_positions() {
+            Player::new(pos).draw(renderer, map_pos, res);
+        }
+
+        let mut cursor = RectangleShape::with_size(Vector2::new(64.0, 64.0));
+        cursor.set_fill_color(&Color::TRANSPARENT);
+        cursor.set_outline_color(&res.theme().accent);
+        cursor.set_outline_thickness(3.0);
+        cursor.set_position(map_pos + Vector2f::new((self.cursor.x * 65) as f32, (self.cursor.y * 65) as f32));
+        renderer.rectangle(&cursor);
+
+        self.title_text.draw(renderer);
+        self.info_text.draw(renderer);
+        self.status_text.draw(renderer);
+        self.controls_text.draw(renderer);
+    }
+
+    fn handle_event(&mut self, event: Event, _res: &Resources) -> SceneAction {
+
+        match event {
+            Event::KeyPressed { code: Key::Return, .. } | Event::KeyPressed { code: Key::Escape, .. } if self.editing_title => {
+                self.edit_title(false);
+            },
+
+            Event::TextEntered { unicode } if self.editing_title => self.type_title(unicode),
+
+            // Keys go into the title instead
+            Event::KeyPressed { .. } if self.editing_title => {},
+
+            Event::KeyPressed { code, ctrl, shift, .. } => match code {
+                Key::Left if shift => self.resize(-1, 0),
+                Key::Right if shift => self.resize(1, 0),
+                Key::Up if shift => self.resize(0, -1),
+                Key::Down if shift => self.resize(0, 1),
+
+                Key::Left => self.move_cursor(Direction::Left),
+                Key::Right => self.move_cursor(Direction::Right),
+                Key::Up => self.move_cursor(Direction::Up),
+                Key::Down => self.move_cursor(Direction::Down),
+
+                Key::S if ctrl => self.save(),
+
+                Key::Space => self.paint(),
+                Key::C => self.change_tile(),
+                Key::E => self.change_exit(),
+                Key::LBracket => self.select_brush(false),
+                Key::RBracket => self.select_brush(true),
+                Key::Tab => self.change_brush(),
+
+                Key::Return => self.edit_title(true),
+                Key::G => self.change_objectives(),
+                Key::M => self.toggle_move_limit(),
+                Key::Dash => self.adjust_move_limit(-1),
+                Key::Equal => self.adjust_move_limit(1),
+
+                Key::P => return self.play(),
+                Key::H => self.solve(),
+
+                Key::Escape => return SceneAction::Pop(1),
+                _ => {},
+            },
+
+            Event::MouseButtonPressed { button, x, y } => {
+                if let Some(pos) = self.tile_map.tile_at_point(self.tile_map.centred_position(), x, y) {
+                    self.cursor = pos;
+
+                    match button {
+                        Button::Left => {
+                            self.painting = true;
+                            self.paint();
+                        },
+                        Button::Right => self.change_tile(),
+                        _ => {},
+                    }
+                }
+            },
+
+            Event::MouseButtonReleased { button: Button::Left, .. } => self.painting = false,
+
+            Event::MouseMoved { x, y } => {
+                if let Some(pos) = self.tile_map.tile_at_point(self.tile_map.centred_position(), x, y) {
+                    self.cursor = pos;
+
+                    if self.painting {
+                        self.paint();
+                    }
+                }
+            },
+
+            Event::MouseWheelScrolled { delta, .. } => self.select_brush(delta < 0.0),
+
+            _ => {},
+        }
+
+        SceneAction::NoChange
+    }
+
+}
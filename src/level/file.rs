@@ -0,0 +1,380 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use level::{Difficulty, Direction, Goal, KeyColor, LevelInfo, Orientation, Tile, TileMap};
+
+use sfml::system::{Vector2, Vector2i};
+
+// Levels are plain text files in the directory of a level pack (see pack.rs). Each line is a row
+// of the map, with one character per tile:
+//
+//     .  empty            #  wall             S  start            T  target
+//     *  snow             %  cracked ice      O  hole
+//     0-9  teleporter, linked to others with the same digit
+//     a-h  switch, which opens and shuts the doors with the same letter
+//     A-H  door, starting shut
+//     r g y p  key, red, green, yellow or purple
+//     R G Y P  lock, opened by the key of the same colour
+//     s  star, which can be collected on the way to the target
+//     X  a pushable block, on empty ice
+//     ^ v < >  one-way, pointing in the direction it can be crossed
+//     / \  deflector, which turns the player along it
+//     n u { }  conveyor, shaped like the point of an arrow: up, down, left or right
+//     i k j l  rotating one-way, starting up, down, left or right (laid out like arrow keys)
+//
+// Lines starting with `//` are comments, so a row can't start with two `/` deflectors. Every row
+// must be the same length and teleporters must come in pairs. There's a player for each start
+// tile, which all move at once, so there must be at least one start.
+//
+// Lines with spaces in are settings rather than rows:
+//
+//     exit 3 2 left    Sends the player off in a new direction when they come out of the
+//                      teleporter in that column and row, counting from zero
+//     goal visit_all   Something which has to be done to finish the level: `target` for every
+//                      player to be on a target, `visit_all` to light up every target, `stars`
+//                      to collect every star, or `moves 8` to finish in that many moves or fewer
+//     title Thin Ice   The rest of the line is the level's title. Likewise `author`, and `hint`
+//                      for a hint shown before the solver's
+//     tutorial Text    A message shown when the level starts, with a `tutorial` line for each
+//                      line of the message
+//     par 12           How many moves the level should take
+//     difficulty hard  `easy`, `medium`, `hard` or `expert`
+//
+// Every goal has to be met. Levels without a `target`, `visit_all` or `stars` goal are finished
+// by reaching the targets, in which case there must be a target for each player.
+#[derive(Clone)]
+pub struct LevelData {
+    pub name: String, // The file name, without the extension. Progress is saved under this, in its pack.
+    pub tile_map: TileMap,
+    pub blocks: Vec<Vector2i>, // Where each block starts
+    pub goals: Vec<Goal>,
+    pub info: LevelInfo,
+}
+
+impl LevelData {
+
+    // What's wrong with the file is given if it can't be loaded, e.g. "Unknown tile 'q' on line 3
+    // of packs/mine/01.level".
+    pub fn from_file(path: &Path) -> Result<LevelData, String> {
+        let display = path.display();
+
+        let file = File::open(path).map_err(|_| format!("Could not open level {}", display))?;
+
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+
+        // Progress is saved under the name, in a file of whitespace-separated fields
+        if name.contains(char::is_whitespace) {
+            return Err(format!("Level {} has a space in its name", display));
+        }
+
+        let mut tiles: Vec<Vec<Tile>> = Vec::new();
+        let mut blocks = Vec::new();
+        let mut exits = Vec::new(); // Line number, position and direction
+        let mut goals = Vec::new();
+        let mut info = LevelInfo::default();
+
+        for (line_num, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|_| format!("Could not read level {}", display))?;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            if fields.len() > 1 {
+                let text = line[fields[0].len()..].trim().to_string();
+                let invalid = |setting: &str| format!("Invalid {} on line {} of {}", setting, line_num + 1, display);
+
+                match fields[0] {
+                    "exit" => exits.push((line_num, Self::parse_exit(&fields[1..]).ok_or_else(|| invalid("exit"))?)),
+                    "goal" => goals.push(Goal::parse(&fields[1..]).ok_or_else(|| invalid("goal"))?),
+                    "title" => info.title = Some(text),
+                    "author" => info.author = Some(text),
+                    "hint" => info.hint = Some(text),
+                    "tutorial" => info.tutorial = Some(match info.tutorial {
+                        Some(tutorial) => format!("{}\n{}", tutorial, text),
+                        None => text,
+                    }),
+                    "par" => info.par = Some(text.parse().map_err(|_| invalid("par"))?),
+                    "difficulty" => info.difficulty = Some(Difficulty::parse(&text).ok_or_else(|| invalid("difficulty"))?),
+                    _ => return Err(format!("Unknown setting '{}' on line {} of {}", fields[0], line_num + 1, display)),
+                }
+
+                continue;
+            }
+
+            let mut row = Vec::new();
+
+            for (x, c) in line.chars().enumerate() {
+                if c == 'X' {
+                    blocks.push(Vector2::new(x as i32, tiles.len() as i32));
+                    row.push(Tile::Empty);
+                    continue;
+                }
+
+                row.push(Tile::from_char(c).ok_or_else(|| format!("Unknown tile '{}' on line {} of {}", c, line_num + 1, display))?);
+            }
+
+            if !tiles.is_empty() && row.len() != tiles[0].len() {
+                return Err(format!("Line {} of {} is a different length to the rows above it", line_num + 1, display));
+            }
+
+            tiles.push(row);
+        }
+
+        if tiles.is_empty() {
+            return Err(format!("Level {} has no tiles", display));
+        }
+
+        let mut tile_map = TileMap { tiles };
+
+        for (line_num, (pos, dir)) in exits {
+            match tile_map.get_tile_at(pos) {
+                Tile::Teleporter(index, _) => tile_map.set_tile_at(pos, Tile::Teleporter(index, Some(dir))),
+                _ => return Err(format!("The exit on line {} of {} isn't on a teleporter", line_num + 1, display)),
+            }
+        }
+
+        if !goals.iter().any(Goal::is_objective) {
+            goals.push(Goal::ReachTarget);
+        }
+
+        let data = LevelData {
+            name,
+            tile_map,
+            blocks,
+            goals,
+            info,
+        };
+
+        data.check().map_err(|problem| format!("Level {} {}", display, problem))?;
+
+        Ok(data)
+    }
+
+    // Whether the level can be played, and what's wrong with it if not, e.g. "has no start tile".
+    pub fn check(&self) -> Result<(), String> {
+        let tiles = || self.tile_map.tiles.iter().flat_map(|row| row.iter());
+
+        let starts = tiles().filter(|tile| **tile == Tile::Start).count();
+        let targets = tiles().filter(|tile| **tile == Tile::Target).count();
+
+        if starts == 0 {
+            return Err("has no start tile".to_string());
+        }
+
+        if self.goals.contains(&Goal::ReachTarget) && targets < starts {
+            return Err(format!("has {} start tiles but only {} targets", starts, targets));
+        }
+
+        if self.goals.contains(&Goal::VisitAll) && targets == 0 {
+            return Err("has to have its targets visited, but has none".to_string());
+        }
+
+        for index in 0..10 {
+            let count = tiles()
+                .filter(|tile| match **tile {
+                    Tile::Teleporter(other_index, _) => other_index == index,
+                    _ => false,
+                })
+                .count();
+
+            if count != 0 && count != 2 {
+                return Err(format!("should have two teleporters numbered {}, but has {}", index, count));
+            }
+        }
+
+        // Only possible from the editor, as it would be read back as a comment
+        let comment = [Tile::Deflector(Orientation::Rising), Tile::Deflector(Orientation::Rising)];
+
+        if self.tile_map.tiles.iter().any(|row| row.starts_with(&comment)) {
+            return Err("has a row starting with two `/` deflectors".to_string());
+        }
+
+        Ok(())
+    }
+
+    // The level in the format it's read in, with blocks on empty ice
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let mut settings = Vec::new();
+
+        for (y, row) in self.tile_map.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let pos = Vector2::new(x as i32, y as i32);
+
+                text.push(if self.blocks.contains(&pos) { 'X' } else { tile.to_char() });
+
+                if let Tile::Teleporter(_, Some(dir)) = *tile {
+                    settings.push(format!("exit {} {} {}", x, y, dir.name()));
+                }
+            }
+
+            text.push('\n');
+        }
+
+        // Levels with no goals are given this one when they're read
+        if self.goals != [Goal::ReachTarget] {
+            for goal in self.goals.iter() {
+                settings.push(format!("goal {}", goal.key()));
+            }
+        }
+
+        let info = &self.info;
+
+        if let Some(ref title) = info.title {
+            settings.push(format!("title {}", title));
+        }
+
+        if let Some(ref author) = info.author {
+            settings.push(format!("author {}", author));
+        }
+
+        if let Some(difficulty) = info.difficulty {
+            settings.push(format!("difficulty {}", difficulty.key()));
+        }
+
+        if let Some(par) = info.par {
+            settings.push(format!("par {}", par));
+        }
+
+        if let Some(ref hint) = info.hint {
+            settings.push(format!("hint {}", hint));
+        }
+
+        if let Some(ref tutorial) = info.tutorial {
+            for line in tutorial.lines() {
+                settings.push(format!("tutorial {}", line));
+            }
+        }
+
+        if !settings.is_empty() {
+            text.push('\n');
+
+            for setting in settings {
+                text.push_str(&setting);
+                text.push('\n');
+            }
+        }
+
+        text
+    }
+
+    // Creates the directory if it doesn't exist yet.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        File::create(path)?.write_all(self.to_text().as_bytes())
+    }
+
+    // `x y direction`
+    fn parse_exit(fields: &[&str]) -> Option<(Vector2i, Direction)> {
+        if fields.len() != 3 {
+            return None;
+        }
+
+        let pos = Vector2::new(fields[0].parse().ok()?, fields[1].parse().ok()?);
+
+        Some((pos, Direction::from_name(fields[2])?))
+    }
+
+}
+
+impl Direction {
+
+    fn from_name(name: &str) -> Option<Direction> {
+        Direction::ALL.iter().find(|dir| dir.name() == name).cloned()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Direction::Up    => "up",
+            Direction::Down  => "down",
+            Direction::Left  => "left",
+            Direction::Right => "right",
+        }
+    }
+
+}
+
+impl Tile {
+
+    fn from_char(c: char) -> Option<Tile> {
+        let tile = match c {
+            '.' => Tile::Empty,
+            '#' => Tile::Wall,
+            'S' => Tile::Start,
+            'T' => Tile::Target,
+            '*' => Tile::Snow,
+            '%' => Tile::Cracked,
+            'O' => Tile::Hole,
+            _ if c >= 'a' && c <= 'h' => Tile::Switch(c as u32 - 'a' as u32),
+            _ if c >= 'A' && c <= 'H' => Tile::Door(c as u32 - 'A' as u32, false),
+            '^' => Tile::OneWay(Direction::Up),
+            'v' => Tile::OneWay(Direction::Down),
+            '<' => Tile::OneWay(Direction::Left),
+            '>' => Tile::OneWay(Direction::Right),
+            '/' => Tile::Deflector(Orientation::Rising),
+            '\\' => Tile::Deflector(Orientation::Falling),
+            'n' => Tile::Conveyor(Direction::Up),
+            'u' => Tile::Conveyor(Direction::Down),
+            '{' => Tile::Conveyor(Direction::Left),
+            '}' => Tile::Conveyor(Direction::Right),
+            'i' => Tile::Rotating(Direction::Up),
+            'k' => Tile::Rotating(Direction::Down),
+            'j' => Tile::Rotating(Direction::Left),
+            'l' => Tile::Rotating(Direction::Right),
+            's' => Tile::Star,
+            'r' => Tile::Key(KeyColor::Red),
+            'g' => Tile::Key(KeyColor::Green),
+            'y' => Tile::Key(KeyColor::Yellow),
+            'p' => Tile::Key(KeyColor::Purple),
+            'R' => Tile::Lock(KeyColor::Red),
+            'G' => Tile::Lock(KeyColor::Green),
+            'Y' => Tile::Lock(KeyColor::Yellow),
+            'P' => Tile::Lock(KeyColor::Purple),
+            _ => Tile::Teleporter(c.to_digit(10)?, None),
+        };
+
+        Some(tile)
+    }
+
+    // The inverse of from_char. Lit targets are written as targets, and doors as shut.
+    fn to_char(&self) -> char {
+        match *self {
+            Tile::Empty => '.',
+            Tile::Wall => '#',
+            Tile::Start => 'S',
+            Tile::Target | Tile::LitTarget => 'T',
+            Tile::Snow => '*',
+            Tile::Cracked => '%',
+            Tile::Hole => 'O',
+            Tile::Switch(id) => (b'a' + id as u8) as char,
+            Tile::Door(id, _) => (b'A' + id as u8) as char,
+            Tile::OneWay(Direction::Up) => '^',
+            Tile::OneWay(Direction::Down) => 'v',
+            Tile::OneWay(Direction::Left) => '<',
+            Tile::OneWay(Direction::Right) => '>',
+            Tile::Deflector(Orientation::Rising) => '/',
+            Tile::Deflector(Orientation::Falling) => '\\',
+            Tile::Conveyor(Direction::Up) => 'n',
+            Tile::Conveyor(Direction::Down) => 'u',
+            Tile::Conveyor(Direction::Left) => '{',
+            Tile::Conveyor(Direction::Right) => '}',
+            Tile::Rotating(Direction::Up) => 'i',
+            Tile::Rotating(Direction::Down) => 'k',
+            Tile::Rotating(Direction::Left) => 'j',
+            Tile::Rotating(Direction::Right) => 'l',
+            Tile::Star => 's',
+            Tile::Key(color) => ['r', 'g', 'y', 'p'][color.index()],
+            Tile::Lock(color) => ['R', 'G', 'Y', 'P'][color.index()],
+            Tile::Teleporter(index, _) => ::std::char::from_digit(index, 10).unwrap(),
+        }
+    }
+
+}
@@ -0,0 +1,90 @@
+use level::TileMap;
+use level::player::Player;
+
+// What has to be done to finish a level. A level is finished once all of its goals are met, and
+// failed if any of them can't be met any more.
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub enum Goal {
+    ReachTarget, // Every player on a target
+    VisitAll, // Every target lit up, by stopping on it
+    Stars, // Every star collected
+    Moves(u32), // Finish in this many moves or fewer
+}
+
+impl Goal {
+
+    // The goal as written in a level file, e.g. `visit_all` or `moves 8`, without the `goal`.
+    pub fn parse(fields: &[&str]) -> Option<Goal> {
+        let goal = match (fields.get(0), fields.len()) {
+            (Some(&"target"), 1) => Goal::ReachTarget,
+            (Some(&"visit_all"), 1) => Goal::VisitAll,
+            (Some(&"stars"), 1) => Goal::Stars,
+            (Some(&"moves"), 2) => Goal::Moves(fields[1].parse().ok()?),
+            _ => return None,
+        };
+
+        Some(goal)
+    }
+
+    // The inverse of parse
+    pub fn key(&self) -> String {
+        match *self {
+            Goal::ReachTarget => "target".to_string(),
+            Goal::VisitAll => "visit_all".to_string(),
+            Goal::Stars => "stars".to_string(),
+            Goal::Moves(moves) => format!("moves {}", moves),
+        }
+    }
+
+    // Whether the goal says anything about where the level ends. Levels which only limit the
+    // number of moves end on the targets.
+    pub fn is_objective(&self) -> bool {
+        match *self {
+            Goal::Moves(_) => false,
+            _ => true,
+        }
+    }
+
+    fn met(&self, players: &[Player], tile_map: &TileMap) -> bool {
+        match *self {
+            Goal::ReachTarget => players.iter().all(Player::reached_target),
+            Goal::VisitAll => tile_map.targets().0 == 0,
+            Goal::Stars => tile_map.stars() == 0,
+            Goal::Moves(moves) => players[0].moves() <= moves,
+        }
+    }
+
+    // Only checked while the goals aren't all met, so running out of moves fails as soon as the
+    // last one is used.
+    fn failed(&self, players: &[Player]) -> bool {
+        match *self {
+            Goal::Moves(moves) => players[0].moves() >= moves,
+            _ => false,
+        }
+    }
+
+    // How far there is to go, for the HUD
+    pub fn progress(&self, players: &[Player], tile_map: &TileMap) -> Option<String> {
+        match *self {
+            Goal::ReachTarget => None,
+            Goal::VisitAll => {
+                let (unlit, lit) = tile_map.targets();
+                Some(format!("Targets lit: {} / {}", lit, unlit + lit))
+            },
+            Goal::Stars => {
+                let collected: u32 = players.iter().map(Player::stars).sum();
+                Some(format!("Stars: {} / {}", collected, collected as usize + tile_map.stars()))
+            },
+            Goal::Moves(moves) => Some(format!("Moves: {} / {}", players[0].moves(), moves)),
+        }
+    }
+
+}
+
+pub fn all_met(goals: &[Goal], players: &[Player], tile_map: &TileMap) -> bool {
+    goals.iter().all(|goal| goal.met(players, tile_map))
+}
+
+pub fn any_failed(goals: &[Goal], players: &[Player], tile_map: &TileMap) -> bool {
+    !all_met(goals, players, tile_map) && goals.iter().any(|goal| goal.failed(players))
+}
@@ -0,0 +1,139 @@
+use resources::Resources;
+use renderer::Renderer;
+use typography::{Label, TextStyle};
+
+use challenge;
+
+use ::{WINDOW_HEIGHT, WINDOW_WIDTH};
+
+// The text drawn over the level: the level with the goals under it in the top-left, the moves,
+// time and best score in the top-right, with the challenge countdown under them, and the controls
+// along the bottom. All but the countdown can be turned off from the settings menu.
+pub struct Hud<'a> {
+    title: Label<'a>, // The level number and title, cut short if it would run into the stats
+    full_title: String,
+    fitted_to: Option<f32>, // The width the title was last cut short to fit. None if it needs fitting again.
+    progress: Label<'a>, // How far there is to go on each goal
+    stats: Label<'a>,
+    challenge: Label<'a>,
+    controls: Label<'a>,
+}
+
+impl<'a> Hud<'a> {
+
+    pub const POSITION: (f32, f32) = (24.0, 16.0);
+    const LINE_HEIGHT: f32 = 28.0;
+    const GAP: f32 = 24.0; // Between the title and the stats
+    const CONTROLS: &'static str = "WASD move   Z undo   R restart   H hint   Esc back";
+
+    pub fn new(res: &'a Resources) -> Hud<'a> {
+        let mut title = Label::new("", TextStyle::Hud, res);
+        title.set_position(Self::POSITION);
+
+        let mut controls = Label::new(Self::CONTROLS, TextStyle::Caption, res);
+        let bounds = controls.local_bounds();
+        controls.set_position(((WINDOW_WIDTH as f32 - bounds.width) / 2.0, WINDOW_HEIGHT as f32 - Self::POSITION.1 - bounds.top - bounds.height));
+
+        let mut progress = Label::new("", TextStyle::Hud, res);
+        progress.set_position((Self::POSITION.0, Self::POSITION.1 + Self::LINE_HEIGHT));
+
+        Hud {
+            title,
+            full_title: String::new(),
+            fitted_to: None,
+            progress,
+            stats: Label::new("", TextStyle::Hud, res),
+            challenge: Label::new("", TextStyle::Hud, res),
+            controls,
+        }
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.full_title = title.to_string();
+        self.fitted_to = None;
+    }
+
+    pub fn set_progress(&mut self, progress: &str) {
+        self.progress.set_string(progress);
+    }
+
+    // Where the keys the players are holding are drawn: after the goals, or in their place if
+    // there are none to show.
+    pub fn inventory_position(&self, res: &Resources) -> (f32, f32) {
+        let progress_width = self.progress.local_bounds().width;
+
+        if res.settings.show_hud.get() && progress_width > 0.0 {
+            (Self::POSITION.0 + progress_width + Self::GAP, Self::POSITION.1 + Self::LINE_HEIGHT)
+        } else {
+            (Self::POSITION.0 - 4.0, Self::POSITION.1 + Self::LINE_HEIGHT)
+        }
+    }
+
+    // Drops letters from the end of the title until it fits in `width`, with "..." after them
+    fn fit_title(&mut self, width: f32) {
+        let mut chars: Vec<char> = self.full_title.chars().collect();
+        self.title.set_string(&self.full_title);
+
+        while self.title.local_bounds().width > width && !chars.is_empty() {
+            chars.pop();
+
+            let shortened: String = chars.iter().collect();
+            self.title.set_string(&format!("{}...", shortened));
+        }
+
+        self.fitted_to = Some(width);
+    }
+
+    // Called every frame, as the time counts up. `best` is the fewest moves the level has been
+    // finished in before, and `challenge` is the countdown, empty when there's no challenge.
+    pub fn update(&mut self, moves: u32, par: Option<u32>, elapsed: u32, best: Option<u32>, challenge: &str, res: &Resources) {
+        let mut stats = format!("Moves: {}", moves);
+
+        if let Some(par) = par {
+            stats.push_str(&format!("   Par: {}", par));
+        }
+
+        stats.push_str(&format!("   Time: {}", challenge::format_time(elapsed)));
+
+        match best {
+            Some(best) => stats.push_str(&format!("   Best: {} moves", best)),
+            None => stats.push_str("   Best: none"),
+        }
+
+        self.stats.set_string(&stats);
+        self.challenge.set_string(challenge);
+
+        // Right-aligned, with the countdown moving up into the corner when the rest is hidden
+        let stats_width = self.stats.local_bounds().width;
+        let stats_x = WINDOW_WIDTH as f32 - stats_width - Self::POSITION.0;
+        self.stats.set_position((stats_x, Self::POSITION.1));
+
+        let title_width = stats_x - Self::GAP - Self::POSITION.0;
+
+        if self.fitted_to != Some(title_width) {
+            self.fit_title(title_width);
+        }
+
+        let challenge_y = if res.settings.show_hud.get() { Self::POSITION.1 + Self::LINE_HEIGHT } else { Self::POSITION.1 };
+        let challenge_width = self.challenge.local_bounds().width;
+        self.challenge.set_position((WINDOW_WIDTH as f32 - challenge_width - Self::POSITION.0, challenge_y));
+
+        self.title.refresh_color(res);
+        self.progress.refresh_color(res);
+        self.stats.refresh_color(res);
+        self.challenge.refresh_color(res);
+        self.controls.refresh_color(res);
+    }
+
+    pub fn draw(&self, renderer: &mut Renderer, res: &Resources) {
+        if res.settings.show_hud.get() {
+            self.title.draw(renderer);
+            self.progress.draw(renderer);
+            self.stats.draw(renderer);
+            self.controls.draw(renderer);
+        }
+
+        self.challenge.draw(renderer);
+    }
+
+}
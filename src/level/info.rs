@@ -0,0 +1,84 @@
+// Everything about a level which isn't part of the puzzle: what it's called, who made it and what to
+// tell the player about it. All of it is optional.
+#[derive(Clone,Debug,Default)]
+pub struct LevelInfo {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub par: Option<u32>, // The number of moves the author expects the level to take
+    pub difficulty: Option<Difficulty>,
+    pub hint: Option<String>, // Shown the first time a hint is asked for, before the solver's
+    pub tutorial: Option<String>, // Shown over the board when the level starts
+}
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl LevelInfo {
+
+    // e.g. "Level 3", or "Level 3: Title" if the level has one
+    pub fn heading(&self, label: &str) -> String {
+        match self.title {
+            Some(ref title) => format!("{}: {}", label, title),
+            None => label.to_string(),
+        }
+    }
+
+    // The title, author, difficulty and par on one line, for the level select. Empty if there are
+    // none.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+
+        if let Some(ref title) = self.title {
+            parts.push(title.clone());
+        }
+
+        if let Some(ref author) = self.author {
+            parts.push(format!("by {}", author));
+        }
+
+        if let Some(difficulty) = self.difficulty {
+            parts.push(difficulty.name().to_string());
+        }
+
+        if let Some(par) = self.par {
+            parts.push(format!("Par: {}", par));
+        }
+
+        parts.join("   ")
+    }
+
+}
+
+impl Difficulty {
+
+    pub const ALL: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard, Difficulty::Expert];
+
+    pub fn parse(key: &str) -> Option<Difficulty> {
+        Self::ALL.iter().find(|difficulty| difficulty.key() == key).cloned()
+    }
+
+    // How the difficulty is written in a level file
+    pub fn key(&self) -> &'static str {
+        match *self {
+            Difficulty::Easy   => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard   => "hard",
+            Difficulty::Expert => "expert",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Difficulty::Easy   => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard   => "Hard",
+            Difficulty::Expert => "Expert",
+        }
+    }
+
+}
@@ -0,0 +1,144 @@
+use std::f32::consts::PI;
+
+use resources::Resources;
+use renderer::Renderer;
+
+use sfml::system::{Vector2, Vector2f};
+use sfml::graphics::{Color, IntRect, PrimitiveType, Transform, Vertex, VertexArray};
+
+// The static geometry of a board, built once when the map (or how it should look) changes, so that
+// drawing the board every frame only takes a few draw calls. Positions are relative to the
+// top-left of the map.
+pub struct BoardMesh {
+    pub tiles: VertexArray, // Procedurally drawn tiles
+    pub sprites: VertexArray, // Tiles from the tileset atlas
+    pub overlay: VertexArray, // Grid lines and tile shapes, drawn over both of the above
+}
+
+impl BoardMesh {
+
+    pub fn new() -> BoardMesh {
+        BoardMesh {
+            tiles: VertexArray::new(PrimitiveType::Triangles, 0),
+            sprites: VertexArray::new(PrimitiveType::Triangles, 0),
+            overlay: VertexArray::new(PrimitiveType::Triangles, 0),
+        }
+    }
+
+    pub fn draw(&self, renderer: &mut Renderer, position: Vector2f, res: &Resources) {
+        let mut transform = Transform::IDENTITY;
+        transform.translate(position.x, position.y);
+
+        renderer.vertices(&self.tiles, transform, None);
+
+        if let Some(ref tileset) = res.tileset {
+            renderer.vertices(&self.sprites, transform, Some(tileset.texture()));
+        }
+
+        renderer.vertices(&self.overlay, transform, None);
+    }
+
+}
+
+pub fn push_triangle(vertices: &mut VertexArray, points: [Vector2f; 3], color: Color) {
+    for point in points.iter() {
+        vertices.append(&Vertex::with_pos_color(*point, color));
+    }
+}
+
+// Corners are given clockwise (or anticlockwise) from any corner.
+pub fn push_quad(vertices: &mut VertexArray, corners: [Vector2f; 4], color: Color) {
+    push_triangle(vertices, [corners[0], corners[1], corners[2]], color);
+    push_triangle(vertices, [corners[0], corners[2], corners[3]], color);
+}
+
+pub fn push_rect(vertices: &mut VertexArray, position: Vector2f, size: Vector2f, color: Color) {
+    push_quad(vertices, [
+        position,
+        Vector2::new(position.x + size.x, position.y),
+        Vector2::new(position.x + size.x, position.y + size.y),
+        Vector2::new(position.x, position.y + size.y),
+    ], color);
+}
+
+// A rectangle centred on `centre`, rotated clockwise by `degrees`.
+pub fn push_rotated_rect(vertices: &mut VertexArray, centre: Vector2f, size: Vector2f, degrees: f32, color: Color) {
+    let (half_w, half_h) = (size.x / 2.0, size.y / 2.0);
+
+    push_quad(vertices, [
+        rotate_about(Vector2::new(centre.x - half_w, centre.y - half_h), centre, degrees),
+        rotate_about(Vector2::new(centre.x + half_w, centre.y - half_h), centre, degrees),
+        rotate_about(Vector2::new(centre.x + half_w, centre.y + half_h), centre, degrees),
+        rotate_about(Vector2::new(centre.x - half_w, centre.y + half_h), centre, degrees),
+    ], color);
+}
+
+pub fn push_line(vertices: &mut VertexArray, from: Vector2f, to: Vector2f, thickness: f32, color: Color) {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let length = (dx * dx + dy * dy).sqrt();
+
+    let centre = Vector2::new((from.x + to.x) / 2.0, (from.y + to.y) / 2.0);
+    let degrees = dy.atan2(dx) * 180.0 / PI;
+
+    push_rotated_rect(vertices, centre, Vector2::new(length, thickness), degrees, color);
+}
+
+// An annulus, or a disc when `inner_radius` is 0.
+pub fn push_ring(vertices: &mut VertexArray, centre: Vector2f, inner_radius: f32, outer_radius: f32, color: Color) {
+    const SEGMENTS: u32 = 30;
+
+    let point = |radius: f32, segment: u32| -> Vector2f {
+        let angle = segment as f32 / SEGMENTS as f32 * 2.0 * PI;
+        Vector2::new(centre.x + radius * angle.cos(), centre.y + radius * angle.sin())
+    };
+
+    for segment in 0..SEGMENTS {
+        push_quad(vertices, [
+            point(inner_radius, segment),
+            point(outer_radius, segment),
+            point(outer_radius, segment + 1),
+            point(inner_radius, segment + 1),
+        ], color);
+    }
+}
+
+// A five-pointed star, pointing up.
+pub fn push_star(vertices: &mut VertexArray, centre: Vector2f, outer_radius: f32, inner_radius: f32, color: Color) {
+    let point = |index: u32| -> Vector2f {
+        let radius = if index % 2 == 0 { outer_radius } else { inner_radius };
+        let angle = (index as f32 / 10.0 - 0.25) * 2.0 * PI;
+        Vector2::new(centre.x + radius * angle.cos(), centre.y + radius * angle.sin())
+    };
+
+    for index in 0..10 {
+        push_triangle(vertices, [centre, point(index), point(index + 1)], color);
+    }
+}
+
+// A 64x64 tile textured with `region` of the atlas, rotated clockwise by `degrees` about its centre.
+pub fn push_sprite(vertices: &mut VertexArray, position: Vector2f, region: &IntRect, degrees: f32) {
+    let centre = Vector2::new(position.x + 32.0, position.y + 32.0);
+
+    let corners = [
+        (Vector2::new(position.x, position.y), Vector2::new(region.left, region.top)),
+        (Vector2::new(position.x + 64.0, position.y), Vector2::new(region.left + region.width, region.top)),
+        (Vector2::new(position.x + 64.0, position.y + 64.0), Vector2::new(region.left + region.width, region.top + region.height)),
+        (Vector2::new(position.x, position.y + 64.0), Vector2::new(region.left, region.top + region.height)),
+    ];
+
+    let vertex = |index: usize| -> Vertex {
+        let (pos, tex) = corners[index];
+        Vertex::new(rotate_about(pos, centre, degrees), Color::WHITE, Vector2::new(tex.x as f32, tex.y as f32))
+    };
+
+    for &index in [0, 1, 2, 0, 2, 3].iter() {
+        vertices.append(&vertex(index));
+    }
+}
+
+pub fn rotate_about(point: Vector2f, centre: Vector2f, degrees: f32) -> Vector2f {
+    let (sin, cos) = (degrees * PI / 180.0).sin_cos();
+    let (x, y) = (point.x - centre.x, point.y - centre.y);
+
+    Vector2::new(centre.x + x * cos - y * sin, centre.y + x * sin + y * cos)
+}
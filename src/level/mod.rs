@@ -1,25 +1,85 @@
 mod player;
 use self::player::Player;
 
+mod block;
+use self::block::Block;
+
+mod door;
+use self::door::Door;
+
+mod rotating;
+use self::rotating::RotatingArrow;
+
+pub mod tileset;
+
+pub mod mesh;
+use self::mesh::BoardMesh;
+
+mod solver;
+
+mod goal;
+pub use self::goal::Goal;
+
+mod hud;
+use self::hud::Hud;
+
+mod info;
+pub use self::info::{Difficulty, LevelInfo};
+
+mod file;
+pub use self::file::LevelData;
+
+mod pack;
+pub use self::pack::LevelPack;
+
+mod editor;
+pub use self::editor::Editor;
+
 use resources::Resources;
 
-use scene::{Scene, SceneAction, Sceneable};
+use scene::{SceneAction, Sceneable};
+
+use typography::{Label, TextStyle};
+
+use theme::Theme;
+
+use renderer::Renderer;
 
 use sfml::window::{Event, Key};
 use sfml::system::{Vector2i, Vector2f, Vector2};
-use sfml::graphics::{Shape, RectangleShape, RenderWindow, ConvexShape, Color, Transformable, RenderTarget};
+use sfml::graphics::{Shape, RectangleShape, Color, PrimitiveType, Transform, Transformable, VertexArray};
 
-#[derive(Copy,Clone,PartialEq)]
+use accessibility::ColorVision;
+
+use save::{ChallengeRecord, LevelRecord, SaveData};
+
+use challenge::{self, Challenge};
+
+use ::{WINDOW_HEIGHT, WINDOW_WIDTH};
+
+#[derive(Copy,Clone,PartialEq,Eq,Hash)]
 pub enum Tile {
     Wall,
-    Teleporter(u32), // Index of the teleporter. Teleporters with same index will be linked.
+    Teleporter(u32, Option<Direction>), // Index of the teleporter. Teleporters with same index will be linked. Some send the player off in a new direction.
     Empty,
     Start,
     OneWay(Direction),
     Target,
+    LitTarget, // A target which a player has stopped on
+    Cracked, // Breaks into a wall once the player has slid off it.
+    Snow, // Stops the player sliding.
+    Hole, // The player falls in, and has to try the level again.
+    Switch(u32), // Opens or shuts every door with the same id when the player slides onto it.
+    Door(u32, bool), // Id of the switches which work it, and whether it's open. Shut doors act as walls.
+    Key(KeyColor), // Picked up when the player slides over it
+    Lock(KeyColor), // A wall, until the player slides into it holding a key of the same colour
+    Star, // Collected when the player slides over it
+    Deflector(Orientation), // Turns the player 90 degrees, without stopping them
+    Conveyor(Direction), // Carries the player a tile in its direction if they stop on it
+    Rotating(Direction), // A one-way which turns a quarter clockwise after every move
 }
 
-#[derive(Copy,Clone,Debug,PartialEq)]
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
 pub enum Direction {
     Up,
     Down,
@@ -27,97 +87,297 @@ pub enum Direction {
     Right
 }
 
-#[derive(Clone)]
+// Which way a deflector's diagonal runs
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+pub enum Orientation {
+    Rising, // `/`, from bottom-left to top-right
+    Falling, // `\`, from top-left to bottom-right
+}
+
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash,PartialOrd,Ord)]
+pub enum KeyColor {
+    Red,
+    Green,
+    Yellow,
+    Purple,
+}
+
+#[derive(Clone,PartialEq,Eq,Hash)]
 pub struct TileMap {
-    tiles: [[Tile; 8]; 5]
+    tiles: Vec<Vec<Tile>>,
 }
 
 // Change this to a const-generic when it becomes a feature.
-pub struct Level {
-    player: Player,
-    map_id: usize,
+pub struct Level<'a> {
+    players: Vec<Player>, // All moved together
+    data: LevelData, // The level as it was loaded, for restarting
+    tile_map: TileMap,
+    blocks: Vec<Block>,
+    doors: Vec<Door>,
+    arrows: Vec<RotatingArrow>, // On the rotating one-ways
+    history: Vec<Snapshot>, // The state before each move, for undoing
+    pack_id: Option<usize>, // None when play-testing from the editor, in which case nothing is saved
+    map_id: usize, // Within the pack
+    hud: Hud<'a>,
+    hint_shown: bool, // Whether the level's own hint has been shown, so the next comes from the solver
+    message_text: Label<'a>,
+    message: Option<Message>, // Shown over the board until a key is pressed
+    fail_title: Label<'a>, // Shown over the board after falling down a hole or running out of moves
+    fail_hint: Label<'a>,
+    complete_title: Label<'a>, // Shown over the board once the target is reached
+    complete_text: Label<'a>,
+    completed: Option<u32>, // The rating out of three, once the level is complete
+    challenge: Challenge,
+    move_budget: Option<u32>, // Set by the challenge, if it limits moves and the level can be solved
+    time_limit: Option<u32>, // In frames, likewise
+    elapsed: u32, // Frames since the level was started or restarted
+    hovered_tile: Option<Vector2i>,
+    board: BoardMesh,
+    board_look: Option<BoardLook>, // What the board mesh was built for. None if it needs rebuilding.
+    label_template: Label<'a>, // Copied for each teleporter number and switch letter
+    tile_labels: Vec<Label<'a>>, // Built with the board mesh
+    frame: u32, // Counts up every update, for animating the board
 }
 
+// What's being shown over the board
+#[derive(Copy,Clone,PartialEq)]
+enum Message {
+    Tutorial, // Pauses the clock, and Escape leaves the level
+    Hint, // The level's own hint
+}
+
+// Everything which a move can change.
+type Snapshot = (Vec<Player>, TileMap, Vec<Block>);
+
+// The settings which change how the board is drawn: the theme, colour vision and tile shapes.
+type BoardLook = (usize, ColorVision, bool);
+
 impl Tile {
 
-    const EMPTY_COLOR: Color = Color { r: 255, g: 255, b: 255, a: 255 };
-    const WALL_COLOR: Color = Color { r: 84, g: 97, b: 112, a: 255 };
-    const TARGET_COLOR: Color = Color { r: 198, g: 243, b: 100, a: 255 };
-    const TELEPORTER_COLOR: Color = Color { r: 255, g: 107, b: 107, a: 255 };
-    const ONEWAY_COLOR: Color = Color { r: 195, g: 77, b: 88, a: 255 };
+    fn build(&self, mesh: &mut BoardMesh, position: Vector2f, res: &Resources) {
+        let theme = res.theme();
+
+        let built_sprite = match res.tileset {
+            Some(ref tileset) => tileset.build_tile(&mut mesh.sprites, self, position),
+            None => false,
+        };
 
-    fn draw(&self, window: &mut RenderWindow, position: Vector2f) {
+        if !built_sprite {
+            self.build_base(&mut mesh.tiles, position, &theme);
+        }
+
+        if res.settings.tile_shapes.get() {
+            self.build_shape(&mut mesh.overlay, position, &theme);
+        }
+    }
 
-        let mut rect = RectangleShape::with_size(Vector2::new(64.0, 64.0));
-        rect.set_position(position);
+    fn build_base(&self, vertices: &mut VertexArray, position: Vector2f, theme: &Theme) {
 
         let color = match *self {
-            Tile::Wall => Self::WALL_COLOR,
-            Tile::Target => Self::TARGET_COLOR,
-            Tile::Teleporter(_) => {
-                // TODO: Change to loop
-                rect.set_fill_color(&Self::TELEPORTER_COLOR);
-                window.draw(&rect);
-
-                rect.set_fill_color(&Self::EMPTY_COLOR);
-                rect.set_size(Vector2::new(52.0, 52.0));
-                rect.move_((6.0, 6.0));
-                window.draw(&rect);
-                
-                rect.set_fill_color(&Self::TELEPORTER_COLOR);
-                rect.set_size(Vector2::new(40.0, 40.0));
-                rect.move_((6.0, 6.0));
-                window.draw(&rect);
-                
-                rect.set_fill_color(&Self::EMPTY_COLOR);
-                rect.set_size(Vector2::new(28.0, 28.0));
-                rect.move_((6.0, 6.0));
-                window.draw(&rect);
-                
-                rect.set_fill_color(&Self::TELEPORTER_COLOR);
-                rect.set_size(Vector2::new(16.0, 16.0));
-                rect.move_((6.0, 6.0));
-                window.draw(&rect);
-
-                rect.set_fill_color(&Self::EMPTY_COLOR);
-                rect.set_size(Vector2::new(4.0, 4.0));
-                rect.move_((6.0, 6.0));
-                window.draw(&rect);
-                
+            Tile::Wall => theme.wall,
+            Tile::Target => theme.target,
+            Tile::LitTarget => {
+                // Glowing from the middle
+                mesh::push_rect(vertices, position, Vector2::new(64.0, 64.0), theme.target);
+                mesh::push_ring(vertices, Vector2::new(position.x + 32.0, position.y + 32.0), 0.0, 20.0, theme.star);
+
+                return;
+            },
+            Tile::Snow => theme.snow,
+            Tile::Teleporter(index, _) => {
+                let pair_color = theme.teleporter(index);
+
+                // Concentric squares, alternating with the empty colour
+                for ring in 0..6 {
+                    let inset = ring as f32 * 6.0;
+                    let size = 64.0 - inset * 2.0;
+                    let ring_color = if ring % 2 == 0 { pair_color } else { theme.empty };
+
+                    mesh::push_rect(vertices, Vector2::new(position.x + inset, position.y + inset), Vector2::new(size, size), ring_color);
+                }
+
                 return;
             },
             Tile::OneWay(dir) => {
-                let rot = dir.get_rot();
+                mesh::push_triangle(vertices, Self::oneway_points(dir, position), theme.oneway);
+
+                return;
+            },
+            Tile::Hole => {
+                mesh::push_ring(vertices, Vector2::new(position.x + 32.0, position.y + 32.0), 0.0, 28.0, theme.hole);
+
+                return;
+            },
+            Tile::Switch(_) => {
+                // A button in a square surround
+                mesh::push_rect(vertices, Vector2::new(position.x + 12.0, position.y + 12.0), Vector2::new(40.0, 40.0), theme.door);
+                mesh::push_rect(vertices, Vector2::new(position.x + 16.0, position.y + 16.0), Vector2::new(32.0, 32.0), theme.empty);
+                mesh::push_ring(vertices, Vector2::new(position.x + 32.0, position.y + 32.0), 0.0, 10.0, theme.door);
+
+                return;
+            },
+            Tile::Conveyor(_) => theme.conveyor, // The arrows move, so are drawn by draw_conveyors
+            Tile::Rotating(_) => {
+                // A track for the arrow to turn around. The arrow itself is drawn by RotatingArrow.
+                mesh::push_ring(vertices, Vector2::new(position.x + 32.0, position.y + 32.0), 27.0, 30.0, theme.oneway);
+
+                return;
+            },
+            Tile::Deflector(orientation) => {
+                let (from, to) = Self::deflector_ends(orientation, position);
+                mesh::push_line(vertices, from, to, 8.0, theme.deflector);
+
+                return;
+            },
+            Tile::Star => {
+                mesh::push_star(vertices, Vector2::new(position.x + 32.0, position.y + 32.0), 22.0, 9.0, theme.star);
 
-                let mut triangle = ConvexShape::new(3);
+                return;
+            },
+            Tile::Key(color) => {
+                Self::build_key(vertices, position, 1.0, theme.key(color.index()), color.index() + 1);
+
+                return;
+            },
+            Tile::Lock(color) => {
+                mesh::push_rect(vertices, position, Vector2::new(64.0, 64.0), theme.key(color.index()));
 
-                triangle.set_point(0, (32.0, 8.0));
-                triangle.set_point(1, (8.0, 56.0));
-                triangle.set_point(2, (56.0, 56.0));
+                // Keyhole
+                mesh::push_ring(vertices, Vector2::new(position.x + 32.0, position.y + 24.0), 0.0, 7.0, theme.board);
+                mesh::push_rect(vertices, Vector2::new(position.x + 29.0, position.y + 26.0), Vector2::new(6.0, 16.0), theme.board);
 
-                triangle.set_origin((32.0, 32.0));
-                triangle.rotate(rot as f32);
+                // One dot per tooth on the matching key, so that colour isn't needed to match them
+                let dots = color.index() + 1;
 
-                triangle.set_fill_color(&Self::ONEWAY_COLOR);
+                for dot in 0..dots {
+                    let x = 32.0 + (dot as f32 - (dots - 1) as f32 / 2.0) * 10.0;
+                    mesh::push_ring(vertices, Vector2::new(position.x + x, position.y + 53.0), 0.0, 3.0, theme.board);
+                }
 
-                triangle.move_((position.x + 32.0, position.y + 32.0));
+                return;
+            },
+            Tile::Cracked => {
+                // Cracks spreading out from near the centre, in the colour the tile will become
+                let point = |x: f32, y: f32| Vector2::new(position.x + x, position.y + y);
 
-                window.draw(&triangle);
+                for &(from, to) in [((28.0, 30.0), (8.0, 12.0)), ((28.0, 30.0), (52.0, 20.0)), ((28.0, 30.0), (36.0, 56.0)),
+                                    ((52.0, 20.0), (60.0, 6.0)), ((36.0, 56.0), (18.0, 50.0))].iter() {
+                    mesh::push_line(vertices, point(from.0, from.1), point(to.0, to.1), 2.0, theme.wall);
+                }
 
                 return;
             },
             _ => return,
         };
 
-        rect.set_fill_color(&color);
-        window.draw(&rect);
+        mesh::push_rect(vertices, position, Vector2::new(64.0, 64.0), color);
 
     }
 
+    // Patterns drawn on top of the tile so that it can be told apart without relying on colour.
+    fn build_shape(&self, vertices: &mut VertexArray, position: Vector2f, theme: &Theme) {
+
+        let centre = Vector2::new(position.x + 32.0, position.y + 32.0);
+
+        match *self {
+            Tile::Wall => {
+                // Diagonal hatching
+                for &(offset, length) in [(-24.0, 48.0), (0.0, 80.0), (24.0, 48.0)].iter() {
+                    let stripe_centre = Vector2::new(centre.x + offset, centre.y - offset);
+                    mesh::push_rotated_rect(vertices, stripe_centre, Vector2::new(length, 4.0), 45.0, theme.board);
+                }
+            },
+            Tile::Target => {
+                // Bullseye
+                mesh::push_ring(vertices, centre, 12.0, 16.0, theme.text);
+                mesh::push_ring(vertices, centre, 0.0, 4.0, theme.text);
+            },
+            Tile::LitTarget => {
+                // Bullseye, filled in
+                mesh::push_ring(vertices, centre, 0.0, 16.0, theme.text);
+            },
+            Tile::Switch(_) => {
+                // A cross on the button
+                mesh::push_rotated_rect(vertices, centre, Vector2::new(16.0, 3.0), 45.0, theme.text);
+                mesh::push_rotated_rect(vertices, centre, Vector2::new(16.0, 3.0), 135.0, theme.text);
+            },
+            Tile::Hole => {
+                // A rim around the edge of the hole
+                mesh::push_ring(vertices, centre, 26.0, 29.0, theme.text);
+            },
+            Tile::Snow => {
+                // Scattered flakes
+                for &(x, y) in [(16.0, 14.0), (44.0, 10.0), (30.0, 30.0), (12.0, 46.0), (50.0, 40.0), (36.0, 54.0)].iter() {
+                    mesh::push_ring(vertices, Vector2::new(position.x + x, position.y + y), 0.0, 3.0, theme.text);
+                }
+            },
+            Tile::OneWay(dir) => {
+                // Outline the arrow so its shape stands out from the board
+                let points = Self::oneway_points(dir, position);
+
+                for i in 0..3 {
+                    mesh::push_line(vertices, points[i], points[(i + 1) % 3], 2.0, theme.text);
+                }
+            },
+            // Teleporters already have a distinct pattern of concentric squares, and deflectors a
+            // diagonal bar.
+            _ => {},
+        }
+
+    }
+
+    pub fn is_target(&self) -> bool {
+        match *self {
+            Tile::Target | Tile::LitTarget => true,
+            _ => false,
+        }
+    }
+
+    // A key in the top-left of a 64x64 tile at `position`, shrunk by `scale`. Keys have a
+    // different number of teeth for each colour.
+    fn build_key(vertices: &mut VertexArray, position: Vector2f, scale: f32, color: Color, teeth: usize) {
+        let point = |x: f32, y: f32| Vector2::new(position.x + x * scale, position.y + y * scale);
+        let size = |w: f32, h: f32| Vector2::new(w * scale, h * scale);
+
+        // Bow, shaft and teeth
+        mesh::push_ring(vertices, point(20.0, 32.0), 6.0 * scale, 12.0 * scale, color);
+        mesh::push_rect(vertices, point(31.0, 29.0), size(24.0, 6.0), color);
+
+        for tooth in 0..teeth {
+            mesh::push_rect(vertices, point(50.0 - tooth as f32 * 7.0, 35.0), size(4.0, 8.0), color);
+        }
+    }
+
+    // The corners which a deflector's diagonal runs between, inset a little
+    fn deflector_ends(orientation: Orientation, position: Vector2f) -> (Vector2f, Vector2f) {
+        let point = |x: f32, y: f32| Vector2::new(position.x + x, position.y + y);
+
+        match orientation {
+            Orientation::Rising => (point(6.0, 58.0), point(58.0, 6.0)),
+            Orientation::Falling => (point(6.0, 6.0), point(58.0, 58.0)),
+        }
+    }
+
+    // An arrow pointing in `dir`
+    fn oneway_points(dir: Direction, position: Vector2f) -> [Vector2f; 3] {
+        Self::arrow_points(dir.get_rot() as f32, position)
+    }
+
+    // An arrow turned `rot` degrees clockwise from pointing up
+    fn arrow_points(rot: f32, position: Vector2f) -> [Vector2f; 3] {
+        let centre = Vector2::new(position.x + 32.0, position.y + 32.0);
+
+        let point = |x: f32, y: f32| mesh::rotate_about(Vector2::new(position.x + x, position.y + y), centre, rot);
+
+        [point(32.0, 8.0), point(8.0, 56.0), point(56.0, 56.0)]
+    }
+
 }
 
 impl Direction {
 
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
     fn get_unit_vec(&self) -> Vector2i {
         match *self {
             Direction::Left  => Vector2::new(-1, 0),
@@ -127,6 +387,16 @@ impl Direction {
         }
     }
 
+    // The direction a quarter turn clockwise from this one
+    fn clockwise(&self) -> Direction {
+        match *self {
+            Direction::Up    => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down  => Direction::Left,
+            Direction::Left  => Direction::Up,
+        }
+    }
+
     fn get_rot(&self) -> f64 {
         match *self {
             Direction::Left  => 270.0,
@@ -138,88 +408,271 @@ impl Direction {
 
 }
 
+impl Orientation {
+
+    // The direction something travelling in `dir` leaves the deflector in
+    fn deflect(&self, dir: Direction) -> Direction {
+        match (*self, dir) {
+            (Orientation::Rising, Direction::Right)  => Direction::Up,
+            (Orientation::Rising, Direction::Up)     => Direction::Right,
+            (Orientation::Rising, Direction::Left)   => Direction::Down,
+            (Orientation::Rising, Direction::Down)   => Direction::Left,
+            (Orientation::Falling, Direction::Right) => Direction::Down,
+            (Orientation::Falling, Direction::Down)  => Direction::Right,
+            (Orientation::Falling, Direction::Left)  => Direction::Up,
+            (Orientation::Falling, Direction::Up)    => Direction::Left,
+        }
+    }
+
+}
+
+impl KeyColor {
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            KeyColor::Red    => "red",
+            KeyColor::Green  => "green",
+            KeyColor::Yellow => "yellow",
+            KeyColor::Purple => "purple",
+        }
+    }
+
+    fn index(&self) -> usize {
+        match *self {
+            KeyColor::Red    => 0,
+            KeyColor::Green  => 1,
+            KeyColor::Yellow => 2,
+            KeyColor::Purple => 3,
+        }
+    }
+
+}
+
 impl TileMap {
 
-    // Maps
-    pub const MAPS: [TileMap; 3] = [
-        TileMap {
-            tiles: [
-                [Tile::Start, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Wall, Tile::Empty, Tile::Empty],
-                [Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty],
-                [Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty],
-                [Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Target, Tile::Empty, Tile::Empty, Tile::Empty],
-                [Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty],
-            ],
-        },
-
-        TileMap {
-            tiles: [
-                [Tile::Empty, Tile::Empty, Tile::Empty, Tile::Start, Tile::Empty, Tile::OneWay(Direction::Right), Tile::Empty, Tile::Empty],
-                [Tile::Empty, Tile::Wall, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty],
-                [Tile::Empty, Tile::Empty, Tile::Target, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Wall, Tile::Empty],
-                [Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty],
-                [Tile::Empty, Tile::Empty, Tile::Empty, Tile::Wall, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty],
-            ],
-        },
-
-        TileMap {
-            tiles: [
-                [Tile::Empty, Tile::Empty, Tile::Wall, Tile::Start, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty],
-                [Tile::Teleporter(0), Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty],
-                [Tile::Empty, Tile::Wall, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty],
-                [Tile::Empty, Tile::Empty, Tile::Target, Tile::Teleporter(0), Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty],
-                [Tile::Empty, Tile::Empty, Tile::OneWay(Direction::Right), Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty, Tile::Empty],
-            ],
-        },
-
-    ];
-
-    const GRID_LINE_COLOR: Color = Color {
-        r: 128,
-        g: 128,
-        b: 128,
-        a: 64,
-    };
-
-    pub fn new(map_id: usize) -> TileMap {
-        Self::MAPS[map_id].clone()
-    }
-
-    fn draw(&self, window: &mut RenderWindow, position: Vector2f) {
-
-        for (y, &row) in self.tiles.iter().enumerate() {
+    // Everything about the board which doesn't change from frame to frame. Positions are relative
+    // to the top-left tile.
+    fn build_mesh(&self, res: &Resources) -> BoardMesh {
+
+        let mut mesh = BoardMesh::new();
+
+        let map_width = self.tiles[0].len() as f32 * 65.0;
+        let map_height = self.tiles.len() as f32 * 65.0;
+
+        mesh::push_rect(&mut mesh.tiles, Vector2::new(-32.0, -32.0), Vector2::new(map_width + 65.0, map_height + 65.0), res.theme().board);
+
+        for (y, row) in self.tiles.iter().enumerate() {
 
             for (x, &tile) in row.iter().enumerate() {
 
-                tile.draw(window, Vector2::new( (x * 65) as f32 + position.x, (y * 65) as f32 + position.y))
+                tile.build(&mut mesh, Vector2::new( (x * 65) as f32, (y * 65) as f32), res)
 
             }
 
         }
 
-        let mut rect = RectangleShape::new();
-        rect.set_fill_color(&Self::GRID_LINE_COLOR);
+        let theme = res.theme();
 
         for y in 1..self.tiles.len() {
-            rect.set_size((self.tiles[0].len() as f32 * 65.0, 1.0));
-            rect.set_position((position.x, (y * 65) as f32 - 1.0 + position.y));
-
-            window.draw(&rect);
+            mesh::push_rect(&mut mesh.overlay, Vector2::new(0.0, (y * 65) as f32 - 1.0), Vector2::new(map_width, 1.0), theme.grid_line);
         }
 
         for x in 1..self.tiles[0].len() {
-            rect.set_size((1.0, self.tiles.len() as f32 * 65.0));
-            rect.set_position(((x * 65) as f32 - 1.0 + position.x, position.y));
+            mesh::push_rect(&mut mesh.overlay, Vector2::new((x * 65) as f32 - 1.0, 0.0), Vector2::new(1.0, map_height), theme.grid_line);
+        }
+
+        // Arrows on the teleporters which turn the player, pointing out of the side they're sent off
+        // from. These go over the top so that they show on sprites too.
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                if let Tile::Teleporter(_, Some(dir)) = *tile {
+                    let position = Vector2::new((x * 65) as f32, (y * 65) as f32);
+                    let centre = Vector2::new(position.x + 32.0, position.y + 32.0);
+
+                    let point = |px: f32, py: f32| mesh::rotate_about(Vector2::new(position.x + px, position.y + py), centre, dir.get_rot() as f32);
+
+                    mesh::push_triangle(&mut mesh.overlay, [point(32.0, 1.0), point(20.0, 15.0), point(44.0, 15.0)], theme.oneway);
+                }
+            }
+        }
+
+        if self.teleporter_pairs() > 1 {
+            // Backing for the numbers made by build_labels
+            for (y, row) in self.tiles.iter().enumerate() {
+                for (x, tile) in row.iter().enumerate() {
+                    if let Tile::Teleporter(..) = *tile {
+                        mesh::push_rect(&mut mesh.overlay, Vector2::new((x * 65) as f32 + 20.0, (y * 65) as f32 + 20.0), Vector2::new(24.0, 24.0), theme.empty);
+                    }
+                }
+            }
+        }
+
+        mesh
+
+    }
+
+    // Where the top-left of the map is drawn, so that the map is centered in the window.
+    fn centred_position(&self) -> Vector2f {
+        let map_width = self.tiles[0].len() as f32 * 65.0;
+        let map_height = self.tiles.len() as f32 * 65.0;
+
+        // TODO: Remove hard-coded screen resolution.
+        Vector2::new((960.0 - map_width) / 2.0, (640.0 - map_height) / 2.0)
+    }
+
+    // The tile under a point in the window, when the map is drawn at `position`
+    fn tile_at_point(&self, position: Vector2f, x: i32, y: i32) -> Option<Vector2i> {
+        let tile_x = ((x as f32 - position.x) / 65.0).floor() as i32;
+        let tile_y = ((y as f32 - position.y) / 65.0).floor() as i32;
+
+        if tile_x < 0 || tile_y < 0 || tile_y as usize >= self.tiles.len() || tile_x as usize >= self.tiles[0].len() {
+            return None;
+        }
+
+        Some(Vector2::new(tile_x, tile_y))
+    }
+
+    fn teleporter_pairs(&self) -> usize {
+        let mut indices: Vec<u32> = Vec::new();
+
+        for row in self.tiles.iter() {
+            for tile in row.iter() {
+                if let Tile::Teleporter(index, _) = *tile {
+                    if !indices.contains(&index) {
+                        indices.push(index);
+                    }
+                }
+            }
+        }
+
+        indices.len()
+    }
+
+    fn switch_ids(&self) -> usize {
+        let mut ids: Vec<u32> = Vec::new();
+
+        for row in self.tiles.iter() {
+            for tile in row.iter() {
+                if let Tile::Switch(id) = *tile {
+                    if !ids.contains(&id) {
+                        ids.push(id);
+                    }
+                }
+            }
+        }
+
+        ids.len()
+    }
+
+    // Numbers each teleporter with its index, and letters switches and doors with their id as in
+    // level files, so that they can be matched up without relying on colour. Each is only needed
+    // when the map has more than one set. The labels are copies of `template`, placed for the map
+    // drawn at `position`, and are built along with the board as they don't change between frames.
+    fn build_labels<'a>(&self, position: Vector2f, template: &Label<'a>) -> Vec<Label<'a>> {
+        let numbered = self.teleporter_pairs() >= 2;
+        let lettered = self.switch_ids() >= 2;
+
+        let mut labels = Vec::new();
+
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+
+                let string = match *tile {
+                    Tile::Teleporter(index, _) if numbered => (index + 1).to_string(),
+                    Tile::Switch(id) | Tile::Door(id, _) if lettered => ((b'A' + id as u8) as char).to_string(),
+                    _ => continue,
+                };
+
+                let centre = Vector2::new((x * 65) as f32 + position.x + 32.0, (y * 65) as f32 + position.y + 32.0);
+
+                let mut label = template.clone();
+                label.set_string(&string);
+
+                let bounds = label.local_bounds();
+                label.set_position((centre.x - bounds.left - bounds.width / 2.0, centre.y - bounds.top - bounds.height / 2.0));
+
+                labels.push(label);
+
+            }
+        }
 
-            window.draw(&rect);
+        labels
+    }
+
+    // Outlines the hovered teleporter and every teleporter linked to it.
+    fn draw_teleporter_links(&self, renderer: &mut Renderer, position: Vector2f, hovered: Vector2i, res: &Resources) {
+
+        let index = match self.get_tile_at(hovered) {
+            Tile::Teleporter(index, _) => index,
+            _ => return,
+        };
+
+        let mut outline = RectangleShape::with_size(Vector2::new(58.0, 58.0));
+        outline.set_fill_color(&Color::TRANSPARENT);
+        outline.set_outline_color(&res.theme().accent);
+        outline.set_outline_thickness(3.0);
+
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+
+                if let Tile::Teleporter(other_index, _) = *tile {
+                    if other_index == index {
+                        outline.set_position(((x * 65) as f32 + position.x + 3.0, (y * 65) as f32 + position.y + 3.0));
+                        renderer.rectangle(&outline);
+                    }
+                }
+
+            }
         }
 
     }
 
+    // Arrows which scroll along each conveyor, fading in and out at the edges of the tile.
+    fn draw_conveyors(&self, renderer: &mut Renderer, position: Vector2f, frame: u32, res: &Resources) {
+        const SPACING: f32 = 24.0;
+
+        let theme = res.theme();
+        let mut vertices = VertexArray::new(PrimitiveType::Triangles, 0);
+
+        // How far along the arrows are, moving a pixel a frame
+        let phase = (frame % (SPACING * 2.0) as u32) as f32;
+
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+
+                let dir = match *tile {
+                    Tile::Conveyor(dir) => dir,
+                    _ => continue,
+                };
+
+                let centre = Vector2::new((x * 65) as f32 + 32.0, (y * 65) as f32 + 32.0);
+
+                for arrow in 0..2 {
+                    // From -SPACING at the back of the tile to SPACING at the front
+                    let along = (phase + arrow as f32 * SPACING) % (SPACING * 2.0) - SPACING;
+                    let alpha = (255.0 * (1.0 - along.abs() / SPACING)) as u8;
+                    let color = Color { a: alpha, ..theme.board };
+
+                    // Drawn pointing up, then turned to face `dir`
+                    let point = |px: f32, py: f32| mesh::rotate_about(Vector2::new(centre.x + px, centre.y + py - along), centre, dir.get_rot() as f32);
+
+                    mesh::push_line(&mut vertices, point(-12.0, 6.0), point(0.0, -6.0), 4.0, color);
+                    mesh::push_line(&mut vertices, point(0.0, -6.0), point(12.0, 6.0), 4.0, color);
+                }
+
+            }
+        }
+
+        let mut transform = Transform::IDENTITY;
+        transform.translate(position.x, position.y);
+
+        renderer.vertices(&vertices, transform, None);
+    }
+
     fn get_tile<F>(&self, is_tile: F) -> Option<Vector2i>
         where F: Fn(&Tile, Vector2i) -> bool {
 
-        for (y, &row) in self.tiles.iter().enumerate() {
+        for (y, row) in self.tiles.iter().enumerate() {
             for (x, &tile) in row.iter().enumerate() {
 
                 let tile_pos = Vector2::new(x as i32, y as i32);
@@ -235,6 +688,69 @@ impl TileMap {
 
     }
 
+    // Turns cracked ice into a wall. Anything else is left as it is.
+    pub fn break_ice(&mut self, pos: Vector2i) {
+        if self.get_tile_at(pos) == Tile::Cracked {
+            self.set_tile_at(pos, Tile::Wall);
+        }
+    }
+
+    // How many targets haven't been lit yet, and how many have
+    pub fn targets(&self) -> (usize, usize) {
+        let tiles = || self.tiles.iter().flat_map(|row| row.iter());
+
+        (tiles().filter(|tile| **tile == Tile::Target).count(), tiles().filter(|tile| **tile == Tile::LitTarget).count())
+    }
+
+    // How many stars are left to collect
+    pub fn stars(&self) -> usize {
+        self.tiles.iter().flat_map(|row| row.iter()).filter(|tile| **tile == Tile::Star).count()
+    }
+
+    // Opens the shut doors with the given id and shuts the open ones.
+    // Doors with something standing in them are left open, rather than shutting it in a wall.
+    pub fn toggle_doors(&mut self, id: u32, occupied: &[Vector2i]) {
+        for (y, row) in self.tiles.iter_mut().enumerate() {
+            for (x, tile) in row.iter_mut().enumerate() {
+                if let Tile::Door(door_id, open) = *tile {
+                    if door_id == id && !(open && occupied.contains(&Vector2::new(x as i32, y as i32))) {
+                        *tile = Tile::Door(id, !open);
+                    }
+                }
+            }
+        }
+    }
+
+    // Turns every rotating one-way a quarter clockwise.
+    pub fn turn_rotating(&mut self) {
+        for row in self.tiles.iter_mut() {
+            for tile in row.iter_mut() {
+                if let Tile::Rotating(dir) = *tile {
+                    *tile = Tile::Rotating(dir.clockwise());
+                }
+            }
+        }
+    }
+
+    pub fn set_tile_at(&mut self, pos: Vector2i, tile: Tile) {
+        self.tiles[pos.y as usize][pos.x as usize] = tile;
+    }
+
+    // Where each player starts, row by row
+    fn start_positions(&self) -> Vec<Vector2i> {
+        let mut positions = Vec::new();
+
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                if tile == Tile::Start {
+                    positions.push(Vector2::new(x as i32, y as i32));
+                }
+            }
+        }
+
+        positions
+    }
+
     // TODO: Add bounds checking
     fn get_tile_at(&self, pos: Vector2i) -> Tile {
         let x = pos.x as usize;
@@ -252,89 +768,591 @@ impl TileMap {
 }
 
 // TODO: Unhardocde 32
-impl Level {
+impl<'a> Level<'a> {
 
-    const ROUNDED_BG_COLOR: Color = Color::WHITE;
+    const MESSAGE_WIDTH: f32 = 640.0;
 
-    const BG_COLOR: Color = Color {
-        r: 242,
-        g: 242,
-        b: 242,
-        a: 255,
-    };
+    pub fn new(res: &Resources, pack_id: usize, level_id: usize) -> Box<Level> {
+        Self::with_data(res, res.packs[pack_id].levels[level_id].clone(), Some(pack_id), level_id)
+    }
+
+    // Plays a level from the editor. It's played on its own, without a challenge, and isn't saved.
+    pub fn test(res: &Resources, data: LevelData) -> Box<Level> {
+        Self::with_data(res, data, None, 0)
+    }
+
+    fn with_data(res: &Resources, data: LevelData, pack_id: Option<usize>, level_id: usize) -> Box<Level> {
 
-    pub fn new(level_id: usize) -> Box<Level> {
+        let (players, tile_map, blocks) = Self::initial_state(&data);
 
-        let tile_map = &TileMap::MAPS[level_id];
+        let mut fail_title = Label::new("Try again", TextStyle::Title, res);
+        let fail_hint = Label::new("", TextStyle::Body, res);
 
-        let player_pos = tile_map.get_tile(|tile: &Tile, _pos: Vector2i| -> bool {
-            *tile == Tile::Start
-        }).expect("There is no start tile!");
+        let title_width = fail_title.local_bounds().width;
+        fail_title.set_position(((WINDOW_WIDTH as f32 - title_width) / 2.0, WINDOW_HEIGHT as f32 / 2.0 - 80.0));
 
-        Box::new(Level {
-            player: Player::new(player_pos),
+        let label_template = Label::new("", TextStyle::Hud, res);
+
+        let mut level = Level {
+            players,
+            data,
+            board: tile_map.build_mesh(res),
+            tile_labels: tile_map.build_labels(tile_map.centred_position(), &label_template),
+            label_template,
+            doors: Door::find_all(&tile_map),
+            arrows: RotatingArrow::find_all(&tile_map),
+            tile_map,
+            blocks,
+            history: Vec::new(),
+            pack_id,
             map_id: level_id,
-        })
+            hud: Hud::new(res),
+            hint_shown: false,
+            message_text: Label::new("", TextStyle::Body, res),
+            message: None,
+            fail_title,
+            fail_hint,
+            complete_title: Label::new("", TextStyle::Title, res),
+            complete_text: Label::new("", TextStyle::Body, res),
+            completed: None,
+            challenge: Challenge::Off,
+            move_budget: None,
+            time_limit: None,
+            elapsed: 0,
+            hovered_tile: None,
+            board_look: Some(Self::board_look(res)),
+            frame: 0,
+        };
+
+        level.start_challenge(res);
+        level.open_tutorial();
+
+        level.refresh_hud();
+
+        Box::new(level)
+
+    }
+
+    // "Level 3", or "Test" when play-testing
+    fn label(&self) -> String {
+        match self.pack_id {
+            Some(_) => format!("Level {}", self.map_id + 1),
+            None => "Test".to_string(),
+        }
+    }
 
+    fn board_look(res: &Resources) -> BoardLook {
+        (res.settings.theme.get(), res.settings.color_vision.get(), res.settings.tile_shapes.get())
     }
 
-    fn next_map(&mut self) {
+    // The level number and title, with how far there is to go on each goal under them
+    fn refresh_hud(&mut self) {
+        let title = self.data.info.heading(&self.label());
+        let progress: Vec<String> = self.data.goals.iter().filter_map(|goal| goal.progress(&self.players, &self.tile_map)).collect();
+
+        self.hud.set_title(&title);
+        self.hud.set_progress(&progress.join("   "));
+    }
+
+    fn initial_state(data: &LevelData) -> Snapshot {
+        let players = data.tile_map.start_positions().into_iter().map(Player::new).collect();
+        let blocks = data.blocks.iter().map(|&pos| Block::new(pos)).collect();
+
+        (players, data.tile_map.clone(), blocks)
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        (self.players.clone(), self.tile_map.clone(), self.blocks.clone())
+    }
+
+    fn restore(&mut self, (players, tile_map, blocks): Snapshot) {
+        if tile_map != self.tile_map {
+            self.board_look = None;
+        }
+
+        self.players = players;
+        self.tile_map = tile_map;
+        self.blocks = blocks;
+        self.doors = Door::find_all(&self.tile_map);
+        self.arrows = RotatingArrow::find_all(&self.tile_map);
+        self.completed = None;
+
+        self.refresh_hud();
+    }
+
+    // Whether everything has finished moving
+    fn is_ready(&self) -> bool {
+        self.players.iter().all(Player::is_ready) && self.blocks.iter().all(Block::is_ready)
+    }
+
+    // Once every goal has been met, and everything has stopped moving. Falling down a hole on the
+    // way to the last star or target still fails.
+    fn goals_met(&self) -> bool {
+        self.is_ready() && !self.failed() && goal::all_met(&self.data.goals, &self.players, &self.tile_map)
+    }
+
+    // Once any player has fallen down a hole, a goal can't be met any more or the challenge is
+    // lost, and everything has stopped moving
+    fn failed(&self) -> bool {
+        self.is_ready() && (self.players.iter().any(Player::fell) || goal::any_failed(&self.data.goals, &self.players, &self.tile_map) || self.challenge_failed())
+    }
+
+    fn next_map(&mut self, res: &Resources) {
         self.map_id += 1;
 
-        let player_pos = TileMap::MAPS[self.map_id].get_tile(|tile: &Tile, _pos: Vector2i| -> bool {
-            *tile == Tile::Start
-        }).expect("There is no start tile!");
+        if let Some(pack_id) = self.pack_id {
+            self.data = res.packs[pack_id].levels[self.map_id].clone();
+        }
+        self.hint_shown = false;
+
+        let state = Self::initial_state(&self.data);
+        self.restore(state);
+        self.history.clear();
+        self.start_challenge(res);
+        self.open_tutorial();
+    }
+
+    // Puts the level's tutorial message over the board, if it has one.
+    fn open_tutorial(&mut self) {
+        self.message = None;
+
+        if let Some(tutorial) = self.data.info.tutorial.clone() {
+            self.show_message(Message::Tutorial, &format!("{}\n\nPress any key to start", tutorial));
+        }
+    }
+
+    fn show_message(&mut self, message: Message, text: &str) {
+        self.message_text.set_wrapped_string(text, Self::MESSAGE_WIDTH);
 
-        self.player = Player::new(player_pos);
+        let bounds = self.message_text.local_bounds();
+        self.message_text.set_position(((WINDOW_WIDTH as f32 - bounds.width) / 2.0, (WINDOW_HEIGHT as f32 - bounds.height) / 2.0 - bounds.top));
+
+        self.message = Some(message);
+    }
+
+    // Sets the move budget and countdown from the shortest solution to the level.
+    fn start_challenge(&mut self, res: &Resources) {
+        self.challenge = if self.pack_id.is_some() { res.settings.challenge.get() } else { Challenge::Off };
+        self.elapsed = 0;
+
+        let optimum = if self.challenge == Challenge::Off {
+            None
+        } else {
+            let (players, tile_map, blocks) = Self::initial_state(&self.data);
+            solver::solve(&players, &tile_map, &blocks, &self.data.goals).map(|moves| moves.len() as u32)
+        };
+
+        self.move_budget = optimum.and_then(|optimum| self.challenge.move_budget(optimum));
+        self.time_limit = optimum.and_then(|optimum| self.challenge.time_limit(optimum));
+
+        let fail_hint = if self.challenge == Challenge::Off { "Press R to restart or Z to undo" } else { "Press R to try again" };
+        self.fail_hint.set_string(fail_hint);
+
+        let hint_width = self.fail_hint.local_bounds().width;
+        self.fail_hint.set_position(((WINDOW_WIDTH as f32 - hint_width) / 2.0, WINDOW_HEIGHT as f32 / 2.0 + 10.0));
+    }
+
+    // Once the move budget is used up or time has run out, without finishing
+    fn challenge_failed(&self) -> bool {
+        let out_of_moves = self.move_budget.map_or(false, |budget| self.players[0].moves() >= budget);
+        let out_of_time = self.time_limit.map_or(false, |limit| self.elapsed >= limit);
+
+        self.completed.is_none() && (out_of_moves || out_of_time) && !goal::all_met(&self.data.goals, &self.players, &self.tile_map)
+    }
+
+    fn challenge_string(&self) -> String {
+        let mut parts = Vec::new();
+
+        if let Some(budget) = self.move_budget {
+            parts.push(format!("Moves left: {}", budget.saturating_sub(self.players[0].moves())));
+        }
+
+        if let Some(limit) = self.time_limit {
+            parts.push(format!("Time: {}", challenge::format_time(limit.saturating_sub(self.elapsed))));
+        }
+
+        parts.join("   ")
+    }
+
+    fn map_position(&self) -> Vector2f {
+        self.tile_map.centred_position()
+    }
+
+    fn tile_under_mouse(&self, x: i32, y: i32) -> Option<Vector2i> {
+        self.tile_map.tile_at_point(self.map_position(), x, y)
     }
 
     pub fn move_player(&mut self, dir: Direction) {
 
-        self.player.set_direction(dir, /*&self.tile_map*/ &TileMap::MAPS[self.map_id]);
+        if !self.is_ready() || self.failed() || self.completed.is_some() {
+            return;
+        }
+
+        let before = self.snapshot();
+
+        if player::move_all(&mut self.players, dir, &mut self.tile_map, &mut self.blocks) {
+            // Ice may have broken, or a hole been filled in
+            if self.tile_map != before.1 {
+                self.board_look = None;
+            }
+
+            self.history.push(before);
+
+            self.refresh_hud();
+
+            for player in self.players.iter() {
+                for &(id, delay) in player.pressed_switches() {
+                    door::press(&mut self.doors, id, delay);
+                }
+            }
+        }
+
+    }
+
+    fn undo(&mut self) {
+
+        // Challenges can't be undone, so that running out of moves or time can't be taken back
+        if !self.is_ready() || self.challenge != Challenge::Off {
+            return;
+        }
+
+        if let Some(snapshot) = self.history.pop() {
+            self.restore(snapshot);
+        }
+
+    }
+
+    // Puts the level back how it started. This can be undone like any other move, except after
+    // failing or finishing, where undoing goes back to before the last move instead. In a challenge
+    // it starts a new attempt, with a fresh countdown.
+    fn restart(&mut self) {
+
+        if !self.is_ready() {
+            return;
+        }
+
+        if !self.failed() && self.completed.is_none() && self.challenge == Challenge::Off {
+            let before = self.snapshot();
+            self.history.push(before);
+        }
+
+        let state = Self::initial_state(&self.data);
+        self.restore(state);
+        self.elapsed = 0;
+
+    }
+
+    // Out of three: one for finishing, one for collecting every star, and one for doing that in as
+    // few moves as possible.
+    fn rating(stars: u32, total_stars: u32, moves: u32, optimum: Option<u32>) -> u32 {
+        let all_stars = stars == total_stars;
+        let fewest_moves = optimum.map_or(false, |optimum| moves <= optimum);
+
+        1 + all_stars as u32 + (all_stars && fewest_moves) as u32
+    }
+
+    // Rates the attempt, saves it and fills in the level complete screen.
+    fn complete(&mut self, res: &Resources) {
+        let data = &self.data;
+        let pack_id = self.pack_id.map(|pack_id| &res.packs[pack_id].id);
+
+        let stars = self.players.iter().map(Player::stars).sum();
+        let total_stars = data.tile_map.stars() as u32;
+        let moves = self.players[0].moves();
+
+        let (players, tile_map, blocks) = Self::initial_state(data);
+        let optimum = solver::solve_all_stars(&players, &tile_map, &blocks, &self.data.goals).map(|moves| moves.len() as u32);
+
+        let rating = Self::rating(stars, total_stars, moves, optimum);
+
+        let placing = match pack_id {
+            Some(pack_id) => {
+                let mut save = res.save.borrow_mut();
+                save.complete_level(pack_id, &data.name, LevelRecord { rating, stars, moves });
+
+                let placing = match self.challenge {
+                    Challenge::Off => None,
+                    challenge => Some(save.add_challenge_result(challenge, pack_id, &data.name, ChallengeRecord { moves, frames: self.elapsed })),
+                };
+
+                // Not being able to save shouldn't stop the level being finished
+                if let Err(error) = save.write(SaveData::FILE) {
+                    eprintln!("Could not write {}: {}", SaveData::FILE, error);
+                }
+
+                placing
+            },
+            None => None,
+        };
+
+        let mut text = String::new();
+
+        if let Some(ref title) = self.data.info.title {
+            text.push_str(title);
+
+            if let Some(ref author) = self.data.info.author {
+                text.push_str(&format!(" by {}", author));
+            }
+
+            text.push_str("\n\n");
+        }
+
+        text.push_str(&format!("Moves: {}", moves));
+
+        if let Some(par) = self.data.info.par {
+            text.push_str(&format!("   Par: {}", par));
+        }
+
+        if let Some(optimum) = optimum {
+            text.push_str(&format!("   Best possible: {}", optimum));
+        }
+
+        if total_stars > 0 {
+            text.push_str(&format!("\nStars: {} / {}", stars, total_stars));
+        }
+
+        if let (Some(placing), Some(pack_id)) = (placing, pack_id) {
+            let placing = match placing {
+                Some(0) => "a new best!".to_string(),
+                Some(place) => format!("number {} on the leaderboard", place + 1),
+                None => "not on the leaderboard".to_string(),
+            };
+
+            text.push_str(&format!("\n\n{} challenge: {}", self.challenge.name(), placing));
+
+            let save = res.save.borrow();
+
+            for (place, record) in save.leaderboard(self.challenge, pack_id, &data.name).iter().take(3).enumerate() {
+                text.push_str(&format!("\n{}.  {} moves in {}", place + 1, record.moves, challenge::format_time(record.frames)));
+            }
+        }
+
+        text.push_str("\n\nPress Enter to continue or R to replay");
+
+        self.complete_title.set_string(&format!("{} complete", self.label()));
+        self.complete_text.set_string(&text);
+
+        let title_width = self.complete_title.local_bounds().width;
+        self.complete_title.set_position(((WINDOW_WIDTH as f32 - title_width) / 2.0, WINDOW_HEIGHT as f32 / 2.0 - 160.0));
+
+        let text_width = self.complete_text.local_bounds().width;
+        self.complete_text.set_position(((WINDOW_WIDTH as f32 - text_width) / 2.0, WINDOW_HEIGHT as f32 / 2.0 + 10.0));
+
+        self.completed = Some(rating);
+    }
+
+    // Dims everything drawn so far, for showing a message over the level.
+    fn draw_backdrop(renderer: &mut Renderer, res: &Resources) {
+        let mut backdrop = RectangleShape::with_size(Vector2::new(WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32));
+        let mut color = res.theme().level_background;
+        color.a = 200;
+        backdrop.set_fill_color(&color);
+
+        renderer.rectangle(&backdrop);
+    }
+
+    fn draw_complete(&self, renderer: &mut Renderer, rating: u32, res: &Resources) {
+        Self::draw_backdrop(renderer, res);
+
+        self.complete_title.draw(renderer);
+
+        let theme = res.theme();
+        let mut stars = VertexArray::new(PrimitiveType::Triangles, 0);
+
+        for star in 0..3 {
+            let centre = Vector2::new(WINDOW_WIDTH as f32 / 2.0 + (star as f32 - 1.0) * 72.0, WINDOW_HEIGHT as f32 / 2.0 - 40.0);
+            let color = if star < rating { theme.star } else { theme.muted_text };
+
+            mesh::push_star(&mut stars, centre, 30.0, 12.0, color);
+        }
+
+        renderer.vertices(&stars, Transform::IDENTITY, None);
+
+        self.complete_text.draw(renderer);
+    }
+
+    // The keys the players are holding, under the HUD text
+    fn draw_inventory(&self, renderer: &mut Renderer, res: &Resources) {
+
+        let keys: Vec<KeyColor> = self.players.iter().flat_map(|player| player.keys().iter().cloned()).collect();
+
+        if keys.is_empty() {
+            return;
+        }
+
+        let theme = res.theme();
+        let start = self.hud.inventory_position(res);
+        let mut vertices = VertexArray::new(PrimitiveType::Triangles, 0);
+
+        for (slot, key) in keys.iter().enumerate() {
+            let position = Vector2::new(start.0 + slot as f32 * 36.0, start.1);
+            Tile::build_key(&mut vertices, position, 0.5, theme.key(key.index()), key.index() + 1);
+        }
+
+        renderer.vertices(&vertices, Transform::IDENTITY, None);
+
+    }
+
+    // Shows the level's own hint the first time, then the first move of the shortest solution from
+    // where the players are now.
+    fn show_hint(&mut self) {
+
+        if !self.is_ready() {
+            return;
+        }
+
+        // The level's own hint can be long, so is shown over the board
+        if let (Some(hint), false) = (self.data.info.hint.clone(), self.hint_shown) {
+            self.hint_shown = true;
+            self.show_message(Message::Hint, &format!("Hint: {}\n\nPress any key to continue", hint));
+            return;
+        }
+
+        let hint = match solver::solve(&self.players, &self.tile_map, &self.blocks, &self.data.goals) {
+            Some(moves) => format!("Hint: {:?}", moves[0]),
+            _ => "No solution from here, press R to restart".to_string(),
+        };
+
+        let hud = format!("{}   {}", self.data.info.heading(&self.label()), hint);
+        self.hud.set_title(&hud);
 
     }
 
 
 }
 
-impl Sceneable for Level {
+impl<'a> Sceneable for Level<'a> {
+
+    fn update(&mut self, resources: &Resources) -> SceneAction {
+        self.frame = self.frame.wrapping_add(1);
+
+        for player in self.players.iter_mut() {
+            player.update();
+        }
+
+        for block in self.blocks.iter_mut() {
+            block.update();
+        }
+
+        for door in self.doors.iter_mut() {
+            door.update(&self.tile_map);
+        }
+
+        let ready = self.is_ready();
+
+        for arrow in self.arrows.iter_mut() {
+            arrow.update(&self.tile_map, ready);
+        }
+
+        self.fail_title.refresh_color(resources);
+        self.fail_hint.refresh_color(resources);
+        self.complete_title.refresh_color(resources);
+        self.complete_text.refresh_color(resources);
+        self.message_text.refresh_color(resources);
+
+        if self.completed.is_none() && !self.failed() && self.message != Some(Message::Tutorial) {
+            self.elapsed += 1;
+        }
 
-    fn update(&mut self, _resources: &Resources) -> SceneAction {
-        self.player.update();
+        let challenge = self.challenge_string();
+        let best = self.pack_id.and_then(|pack_id| {
+            resources.save.borrow().record(&resources.packs[pack_id].id, &self.data.name).map(|record| record.moves)
+        });
+        self.hud.update(self.players[0].moves(), self.data.info.par, self.elapsed, best, &challenge, resources);
 
-        if self.player.reached_target() {
-            self.next_map();
+        if self.goals_met() && self.completed.is_none() {
+            self.complete(resources);
+        }
+
+        if self.failed() && resources.settings.auto_restart.get() {
+            self.restart();
+        }
+
+        let look = Self::board_look(resources);
+
+        // Wait for everything to finish moving, so that ice doesn't break (or holes fill) too soon.
+        if self.board_look != Some(look) && self.is_ready() {
+            self.board = self.tile_map.build_mesh(resources);
+            self.label_template.refresh_color(resources);
+            self.tile_labels = self.tile_map.build_labels(self.map_position(), &self.label_template);
+            self.board_look = Some(look);
         }
 
         SceneAction::NoChange
     }
 
-    fn draw(&self, window: &mut RenderWindow) {
-        window.clear(&Self::BG_COLOR);
-
-        let map_width = TileMap::MAPS[self.map_id].tiles[0].len() as f32 * 65.0;
-        let map_height = TileMap::MAPS[self.map_id].tiles.len() as f32 * 65.0;
+    fn draw(&self, renderer: &mut Renderer, resources: &Resources) {
+        renderer.fill(&resources.theme().level_background);
 
-        // TODO: Remove hard-coded screen resolution.
-        let map_x = (960.0 - map_width) / 2.0;
-        let map_y = (640.0 - map_height) / 2.0;
+        let map_pos = self.map_position();
+        let map_x = map_pos.x;
+        let map_y = map_pos.y;
 
         // TODO: Use a Transform rather than passing a poition, and use trans.tranform_point on the points.
 
-        let mut rect = RectangleShape::with_size(Vector2::new(map_width + 65.0, map_height + 65.0));
-        rect.set_position(Vector2::new(map_x - 32.0, map_y - 32.0));
-        rect.set_fill_color(&Self::ROUNDED_BG_COLOR);
+        self.board.draw(renderer, map_pos, resources);
+        self.tile_map.draw_conveyors(renderer, map_pos, self.frame, resources);
 
-        window.draw(&rect);
+        for door in self.doors.iter() {
+            door.draw(renderer, map_pos, resources);
+        }
+
+        for arrow in self.arrows.iter() {
+            arrow.draw(renderer, map_pos, resources);
+        }
+
+        for label in self.tile_labels.iter() {
+            label.draw(renderer);
+        }
+
+        if let Some(hovered) = self.hovered_tile {
+            if resources.settings.teleporter_links.get() {
+                self.tile_map.draw_teleporter_links(renderer, Vector2::new(map_x, map_y), hovered, resources);
+            }
+        }
+
+        for block in self.blocks.iter() {
+            block.draw(renderer, Vector2::new(map_x, map_y), resources);
+        }
 
-        TileMap::MAPS[self.map_id].draw(window, Vector2::new(map_x, map_y));
+        for player in self.players.iter() {
+            player.draw(renderer, Vector2::new(map_x, map_y), resources);
+        }
 
-        //self.tile_map.draw(window, Vector2::new(map_x, map_y));
-        self.player.draw(window, Vector2::new(map_x, map_y));
+        self.hud.draw(renderer, resources);
+        self.draw_inventory(renderer, resources);
+
+        if self.failed() && !resources.settings.auto_restart.get() {
+            Self::draw_backdrop(renderer, resources);
+
+            self.fail_title.draw(renderer);
+            self.fail_hint.draw(renderer);
+        }
+
+        if let Some(rating) = self.completed {
+            self.draw_complete(renderer, rating, resources);
+        }
+
+        if self.message.is_some() {
+            Self::draw_backdrop(renderer, resources);
+            self.message_text.draw(renderer);
+        }
 
     }
 
     fn handle_event(&mut self, event: Event, resources: &Resources) -> SceneAction {
+
+        // Any key closes the message without doing anything else, except Escape on the tutorial
+        if let Some(message) = self.message {
+            match event {
+                Event::KeyPressed { code: Key::Escape, .. } if message == Message::Tutorial => return SceneAction::Pop(1),
+                Event::KeyPressed { .. } => self.message = None,
+                _ => {},
+            }
+
+            return SceneAction::NoChange;
+        }
+
         match event {
             Event::KeyPressed { code, .. } => match code {
                 Key::A => self.move_player(Direction::Left),
@@ -342,9 +1360,24 @@ impl Sceneable for Level {
                 Key::W => self.move_player(Direction::Up),
                 Key::S => self.move_player(Direction::Down),
 
-                Key::F => return SceneAction::Change(Scene::MainMenu),
+                Key::Z => self.undo(),
+                Key::R => self.restart(),
+                Key::H => self.show_hint(),
+
+                Key::Return if self.completed.is_some() => {
+                    // Back to the level select after the last level of the pack, or the editor
+                    // after a test
+                    if self.pack_id.map_or(true, |pack_id| self.map_id + 1 == resources.packs[pack_id].levels.len()) {
+                        return SceneAction::Pop(1);
+                    }
+
+                    self.next_map(resources);
+                },
+
+                Key::F | Key::Escape => return SceneAction::Pop(1),
                 _ => {},
             },
+            Event::MouseMoved { x, y } => self.hovered_tile = self.tile_under_mouse(x, y),
             _ => {},
         };
         SceneAction::NoChange
@@ -0,0 +1,253 @@
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use level::LevelData;
+
+use save::SaveData;
+
+// A directory of levels, played in order. Packs come with the game in res/packs, and players can
+// add their own in packs/, next to the save file. Each pack may have a manifest, pack.cfg:
+//
+//     title       Ice Game
+//     author      Someone
+//     level       01
+//     level       02
+//     unlock      after main 8
+//     sequential
+//
+// `level` lines give the levels' file names, without `.level`, in the order they're played.
+// Without any, every level in the directory is played in order of file name. `unlock after`
+// keeps the pack locked until every level of another pack has been finished, or just that many of
+// them if a number is given. The levels of a `sequential` pack are unlocked one at a time, as the
+// one before is finished. The title defaults to the directory name, which progress is saved under,
+// so it can't have spaces in.
+pub struct LevelPack {
+    pub id: String, // The directory name
+    pub title: String,
+    pub author: Option<String>,
+    pub levels: Vec<LevelData>, // In the order they're played
+    pub user: bool, // Added by the player, rather than coming with the game
+    unlocks: Vec<Unlock>,
+    sequential: bool,
+}
+
+// Another pack which has to be played first, and how many of its levels. None for all of them.
+struct Unlock {
+    pack: String,
+    levels: Option<usize>,
+}
+
+impl LevelPack {
+
+    pub const BUILT_IN_DIR: &'static str = "res/packs";
+    pub const USER_DIR: &'static str = "packs";
+
+    const MANIFEST: &'static str = "pack.cfg";
+
+    // The built-in packs followed by the player's own, each in order of directory name. Anything
+    // wrong with a built-in pack stops the game, but the player's own packs and levels are skipped
+    // with the reason printed, so that a broken level doesn't stop the game starting.
+    pub fn load_all() -> Vec<LevelPack> {
+        let mut packs: Vec<LevelPack> = Self::pack_dirs(Self::BUILT_IN_DIR).iter()
+            .map(|dir| Self::from_dir(dir, false).unwrap_or_else(|problem| panic!("{}", problem)))
+            .collect();
+
+        if packs.is_empty() {
+            panic!("There are no level packs in {}", Self::BUILT_IN_DIR);
+        }
+
+        for dir in Self::pack_dirs(Self::USER_DIR) {
+            match Self::from_dir(&dir, true) {
+                Ok(ref pack) if packs.iter().any(|other| other.id == pack.id) => {
+                    eprintln!("Skipping level pack {}, as there's already a pack called {}", dir.display(), pack.id);
+                },
+                Ok(pack) => packs.push(pack),
+                Err(problem) => eprintln!("Skipping level pack {}: {}", dir.display(), problem),
+            }
+        }
+
+        // Skipping a pack may leave another unlocked by it, so keep going until none are left
+        while let Some(index) = packs.iter().position(|pack| pack.user && pack.missing_unlock(&packs).is_some()) {
+            let pack = packs.remove(index);
+            eprintln!("Skipping level pack {}, as it's unlocked by {}, which isn't installed", pack.id, pack.missing_unlock(&packs).unwrap());
+        }
+
+        for pack in packs.iter() {
+            if let Some(missing) = pack.missing_unlock(&packs) {
+                panic!("Level pack {} is unlocked by {}, which isn't installed", pack.id, missing);
+            }
+        }
+
+        packs
+    }
+
+    // Every directory inside is a pack. A missing directory has none.
+    fn pack_dirs(dir: &str) -> Vec<PathBuf> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut paths: Vec<_> = entries.filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+
+        paths.sort();
+
+        paths
+    }
+
+    // Levels which can't be loaded are skipped in the player's own packs, and fail the whole pack
+    // otherwise.
+    pub fn from_dir(dir: &Path, user: bool) -> Result<LevelPack, String> {
+        let id = dir.file_name().unwrap().to_string_lossy().into_owned();
+
+        // Progress is saved under the id, in a file of whitespace-separated fields
+        if id.contains(char::is_whitespace) {
+            return Err(format!("Level pack {} has a space in its name", dir.display()));
+        }
+
+        if id == SaveData::CHALLENGE_TAG {
+            return Err(format!("Level pack {} can't be called {}, as its progress couldn't be saved", dir.display(), id));
+        }
+
+        let mut pack = LevelPack {
+            title: id.clone(),
+            id,
+            author: None,
+            levels: Vec::new(),
+            user,
+            unlocks: Vec::new(),
+            sequential: false,
+        };
+
+        let manifest = dir.join(Self::MANIFEST);
+        let mut names = Vec::new();
+
+        if let Ok(file) = File::open(&manifest) {
+            for (line_num, line) in BufReader::new(file).lines().enumerate() {
+                let line = line.map_err(|_| format!("Could not read {}", manifest.display()))?;
+                let line = line.trim();
+
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+
+                let mut fields = line.splitn(2, char::is_whitespace);
+                let key = fields.next().unwrap();
+                let value = fields.next().unwrap_or("").trim();
+
+                match key {
+                    "title" => pack.title = value.to_string(),
+                    "author" => pack.author = Some(value.to_string()),
+                    "level" => names.push(value.to_string()),
+                    "unlock" => pack.unlocks.push(Unlock::parse(value)
+                        .ok_or_else(|| format!("Invalid unlock rule on line {} of {}", line_num + 1, manifest.display()))?),
+                    "sequential" => pack.sequential = true,
+                    _ => return Err(format!("Unknown setting '{}' on line {} of {}", key, line_num + 1, manifest.display())),
+                }
+            }
+        }
+
+        let paths = if names.is_empty() {
+            Self::level_paths(dir)?
+        } else {
+            names.iter().map(|name| dir.join(format!("{}.level", name))).collect()
+        };
+
+        for path in paths {
+            match LevelData::from_file(&path) {
+                Ok(level) => pack.levels.push(level),
+                Err(ref problem) if user => eprintln!("Skipping: {}", problem),
+                Err(problem) => return Err(problem),
+            }
+        }
+
+        if pack.levels.is_empty() {
+            return Err(format!("Level pack {} has no levels", dir.display()));
+        }
+
+        Ok(pack)
+    }
+
+    // Every level in the directory, in order of file name
+    fn level_paths(dir: &Path) -> Result<Vec<PathBuf>, String> {
+        let entries = fs::read_dir(dir).map_err(|_| format!("Could not read the level directory {}", dir.display()))?;
+
+        let mut paths: Vec<_> = entries.filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "level"))
+            .collect();
+
+        paths.sort();
+
+        Ok(paths)
+    }
+
+    // The first pack it depends on which isn't in `packs`, if any
+    fn missing_unlock(&self, packs: &[LevelPack]) -> Option<&str> {
+        self.unlocks.iter()
+            .find(|unlock| !packs.iter().any(|pack| pack.id == unlock.pack))
+            .map(|unlock| &unlock.pack[..])
+    }
+
+    // How many of the levels have been finished
+    pub fn completed(&self, save: &SaveData) -> usize {
+        self.levels.iter().filter(|level| save.record(&self.id, &level.name).is_some()).count()
+    }
+
+    // The ratings of every level added up, out of three for each
+    pub fn rating(&self, save: &SaveData) -> u32 {
+        self.levels.iter().filter_map(|level| save.record(&self.id, &level.name)).map(|record| record.rating).sum()
+    }
+
+    // Once enough of every pack it depends on has been finished
+    pub fn is_unlocked(&self, packs: &[LevelPack], save: &SaveData) -> bool {
+        self.unlocks.iter().all(|unlock| {
+            packs.iter().find(|pack| pack.id == unlock.pack).map_or(false, |pack| {
+                let needed = unlock.levels.map_or(pack.levels.len(), |levels| levels.min(pack.levels.len()));
+                pack.completed(save) >= needed
+            })
+        })
+    }
+
+    pub fn is_level_unlocked(&self, level_id: usize, save: &SaveData) -> bool {
+        !self.sequential || level_id == 0 || save.record(&self.id, &self.levels[level_id - 1].name).is_some()
+    }
+
+    // What has to be done to unlock the pack, for the pack select
+    pub fn unlock_hint(&self, packs: &[LevelPack]) -> String {
+        let rules: Vec<String> = self.unlocks.iter().map(|unlock| {
+            let title = packs.iter().find(|pack| pack.id == unlock.pack).map_or(&unlock.pack, |pack| &pack.title);
+
+            match unlock.levels {
+                Some(levels) => format!("{} levels of {}", levels, title),
+                None => format!("all of {}", title),
+            }
+        }).collect();
+
+        format!("Finish {} to unlock", rules.join(" and "))
+    }
+
+}
+
+impl Unlock {
+
+    // `after <pack>` or `after <pack> <levels>`, without the `unlock`
+    fn parse(value: &str) -> Option<Unlock> {
+        let fields: Vec<&str> = value.split_whitespace().collect();
+
+        if fields.get(0) != Some(&"after") {
+            return None;
+        }
+
+        match fields.len() {
+            2 => Some(Unlock { pack: fields[1].to_string(), levels: None }),
+            3 => Some(Unlock { pack: fields[1].to_string(), levels: Some(fields[2].parse().ok()?) }),
+            _ => None,
+        }
+    }
+
+}
@@ -1,90 +1,94 @@
 use std::collections::VecDeque;
 
-use level::{Direction, Tile, TileMap};
+use level::{Direction, KeyColor, Tile, TileMap};
+use level::block::{self, Block};
+
+use resources::Resources;
+use renderer::Renderer;
 
 use sfml::system::{Vector2, Vector2f, Vector2i};
-use sfml::graphics::{Color, RenderWindow, RenderTarget, RectangleShape, Shape, Transformable};
+use sfml::graphics::{RectangleShape, Shape, Transformable};
 
-#[derive(Debug)]
+#[derive(Clone,Debug)]
 pub enum Action {
     Move { dir: Direction, steps: u32 },
     Teleport(Vector2i),
+    Fall,
+    Wait(u32), // Frames to do nothing for, e.g. while the player slides up to a block
 }
 
+const SPEED: f32 = 4.0;
+const WARP_FRAMES: u32 = 16;
+const FALL_FRAMES: u32 = 24;
+
+#[derive(Clone)]
 pub struct Player {
     action_queue: VecDeque<Action>,
     action_counter: u32,
     window_pos: Vector2f,
+    scale: f32, // Shrinks to nothing and back while warping between teleporters
     map_pos: Vector2i,
     reached_target: bool,
+    fell: bool, // Down a hole, so the level has to be restarted (or the move undone)
+    pressed_switches: Vec<(u32, u32)>, // Switches slid onto in the last move: the id, and how many frames in
+    keys: Vec<KeyColor>, // Picked up and not yet used, in the order they were picked up
+    stars: u32, // Collected so far
+    moves: u32, // Made since the level was started, counting those where only other players moved
 }
 
 impl Player {
 
-    const SPEED: f32 = 4.0;
-
-    const COLOR: Color = Color {
-        r: 78,
-        g: 204,
-        b: 195,
-        a: 255,
-    };
-
     pub fn new(starting_position: Vector2i) -> Player {
         Player {
             action_queue: VecDeque::new(),
             window_pos: Vector2::new(starting_position.x as f32 * 65.0, starting_position.y as f32 * 65.0),
             action_counter: 0,
+            scale: 1.0,
             map_pos: starting_position,
             reached_target: false,
+            fell: false,
+            pressed_switches: Vec::new(),
+            keys: Vec::new(),
+            stars: 0,
+            moves: 0,
         }
     }
 
     pub fn update(&mut self) {
 
-        if let Some(action) = self.action_queue.front() {
-
-            match *action {
-                Action::Move { dir, steps } => {
-
-                    if self.action_counter == 0 {
-                        self.action_counter = (steps as f32 * 65.0 / Self::SPEED) as u32;
-                    }
-
-                    let unit_vec = dir.get_unit_vec();
-
-                    self.window_pos.x += unit_vec.x as f32 * Self::SPEED;
-                    self.window_pos.y += unit_vec.y as f32 * Self::SPEED;
-
-                    self.action_counter -= 1;
-                },
-                Action::Teleport(pos) => {
-                    self.window_pos.x = pos.x as f32 * 65.0;
-                    self.window_pos.y = pos.y as f32 * 65.0;
-                },
-            }
-
-        }
-
-        if !self.action_queue.is_empty() && self.action_counter == 0 {
-            self.action_queue.pop_front();
-        }
+        animate(&mut self.action_queue, &mut self.action_counter, &mut self.window_pos, &mut self.scale);
 
         if self.action_queue.is_empty() {
             self.window_pos.x = self.map_pos.x as f32 * 65.0;
             self.window_pos.y = self.map_pos.y as f32 * 65.0;
+            self.scale = if self.fell { 0.0 } else { 1.0 };
         }
 
     }
 
-    pub fn draw(&self, window: &mut RenderWindow, position: Vector2f) {
+    pub fn draw(&self, renderer: &mut Renderer, position: Vector2f, res: &Resources) {
+        let theme = res.theme();
+
+        let centre = (position.x + self.window_pos.x + 32.0, position.y + self.window_pos.y + 32.0);
 
         // TODO: Store the rect instead of position?
         let mut rect = RectangleShape::with_size(Vector2::new(64.0, 64.0));
-        rect.set_position((position.x + self.window_pos.x, position.y + self.window_pos.y));
-        rect.set_fill_color(&Self::COLOR);
+        rect.set_origin((32.0, 32.0));
+        rect.set_position(centre);
+        rect.set_scale((self.scale, self.scale));
+        rect.set_fill_color(&theme.player);
 
-        window.draw(&rect);
+        renderer.rectangle(&rect);
+
+        if res.settings.tile_shapes.get() {
+            // A diamond, so the player can't be mistaken for a tile
+            rect.set_size((24.0, 24.0));
+            rect.set_origin((12.0, 12.0));
+            rect.set_rotation(45.0);
+            rect.set_fill_color(&theme.board);
+
+            renderer.rectangle(&rect);
+        }
 
     }
 
@@ -92,44 +96,102 @@ impl Player {
         self.action_queue.push_back(action);
     }
 
-    fn is_ready(&self) -> bool {
+    pub fn is_ready(&self) -> bool {
         self.action_queue.is_empty()
     }
 
-    pub fn set_direction(&mut self, dir: Direction, tile_map: &TileMap) {
+    // Skips the rest of the animation, for when only the outcome of a move matters.
+    pub fn finish_actions(&mut self) {
+        self.action_queue.clear();
+        self.action_counter = 0;
+        self.update();
+    }
+
+    pub fn map_pos(&self) -> Vector2i {
+        self.map_pos
+    }
+
+    // Returns whether the player moved or pushed a block. Cracked ice which is slid off breaks and
+    // holes can be filled in, so the tile map may be changed. Stopping on a conveyor carries the
+    // player on a tile, which may be onto another conveyor. `others` are where the other players
+    // are, which stop this one like walls do.
+    fn set_direction(&mut self, dir: Direction, tile_map: &mut TileMap, blocks: &mut [Block], others: &[Vector2i]) -> bool {
 
         if !self.is_ready() {
-            return;
+            return false;
         }
 
-        if self.reached_target() {
-            return;
+        if self.fell {
+            return false;
         }
 
-        let unit_vec = dir.get_unit_vec();
+        // Players can slide off a target while others are still finding theirs
+        self.reached_target = false;
+
+        let mut dir = dir;
+        let mut unit_vec = dir.get_unit_vec();
 
         let mut count: u32 = 0;
+        let mut pushed = false;
+        let mut visited: Vec<(Vector2i, Direction)> = Vec::new(); // Teleporters and deflectors entered, and which way
+
+        let mut carried_from: Option<Vector2i> = None; // Where a conveyor last picked the player up
+        let mut conveyors: Vec<Vector2i> = Vec::new(); // Which have carried the player this move
+
+        self.pressed_switches.clear();
 
         loop {
-            self.map_pos += unit_vec;
+            loop {
+                // Conveyors only carry the player one tile
+                if let Some(carried_from) = carried_from {
+                    if self.map_pos != carried_from {
+                        if count != 0 {
+                            self.add_action(Action::Move{ dir, steps: count });
+                        }
 
-            match tile_map.get_tile_at(self.map_pos) {
+                        break;
+                    }
+                }
+
+                let from = self.map_pos;
+                self.map_pos += unit_vec;
 
-                Tile::Wall => {
+                if let Some(index) = block::block_at(blocks, self.map_pos) {
                     if count != 0 {
                         self.add_action(Action::Move{ dir, steps: count });
                     }
 
                     self.map_pos -= unit_vec;
 
+                    // The block starts sliding once the player has reached it
+                    let delay = self.action_queue.iter().map(Action::frames).sum();
+                    pushed |= block::push(blocks, index, dir, delay, tile_map, others);
+
                     break;
+                }
 
-                },
+                if others.contains(&self.map_pos) {
+                    if count != 0 {
+                        self.add_action(Action::Move{ dir, steps: count });
+                    }
+
+                    self.map_pos -= unit_vec;
+
+                    break;
+                }
 
-                Tile::OneWay(allowed_dir) => {
-                    if dir == allowed_dir {
+                match tile_map.get_tile_at(self.map_pos) {
+
+                    Tile::Lock(color) if self.keys.contains(&color) => {
+                        let key = self.keys.iter().position(|&key| key == color).unwrap();
+                        self.keys.remove(key);
+
+                        tile_map.set_tile_at(self.map_pos, Tile::Empty);
+                        tile_map.break_ice(from);
                         count += 1;
-                    } else {
+                    },
+
+                    Tile::Wall | Tile::Door(_, false) | Tile::Lock(_) => {
                         if count != 0 {
                             self.add_action(Action::Move{ dir, steps: count });
                         }
@@ -137,48 +199,287 @@ impl Player {
                         self.map_pos -= unit_vec;
 
                         break;
-                    }
-                },
 
-                Tile::Teleporter(index) => {
-                    // If two teleporters are placed in line of sight, they keep passing you between them (and hence freeze the game). Perhaps make it so you can pass through a teleporter once on each move only.
+                    },
+
+                    Tile::OneWay(allowed_dir) | Tile::Rotating(allowed_dir) => {
+                        if dir == allowed_dir {
+                            count += 1;
+                            tile_map.break_ice(from);
+                        } else {
+                            if count != 0 {
+                                self.add_action(Action::Move{ dir, steps: count });
+                            }
+
+                            self.map_pos -= unit_vec;
+
+                            break;
+                        }
+                    },
+
+                    Tile::Teleporter(index, _) => {
+                        let map_pos = self.map_pos;
+                        let is_matching_teleporter = move |tile: &Tile, pos: Vector2i| -> bool {
+                            if let Tile::Teleporter(other_index, _) = *tile {
+                                return other_index == index && pos != map_pos;
+                            }
+                            false
+                        };
+
+                        // Levels with unpaired teleporters are turned away when they're loaded
+                        let destination = tile_map.get_tile(is_matching_teleporter).expect("No matching teleporter!");
+                        tile_map.break_ice(from);
+
+                        // A block or another player sitting on the other teleporter stops it working
+                        if block::block_at(blocks, destination).is_some() || others.contains(&destination) {
+                            count += 1;
+                            continue;
+                        }
 
-                    let map_pos = self.map_pos;
-                    let is_matching_teleporter = move |tile: &Tile, pos: Vector2i| -> bool {
-                        if let Tile::Teleporter(other_index) = *tile {
-                            return other_index == index && pos != map_pos;
+                        // Teleporters in line of sight of each other would keep passing the player
+                        // between them forever
+                        if visited.contains(&(self.map_pos, dir)) {
+                            self.add_action(Action::Move{ dir, steps: count + 1 });
+                            break;
                         }
-                        false
-                    };
 
-                    self.map_pos = tile_map.get_tile(is_matching_teleporter).expect("No matching teleporter!");
+                        visited.push((self.map_pos, dir));
 
-                    self.add_action(Action::Move{ dir, steps: count + 1} );
-                    count = 0;
+                        self.map_pos = destination;
 
-                    let map_pos = self.map_pos;
-                    self.add_action(Action::Teleport(map_pos));
+                        self.add_action(Action::Move{ dir, steps: count + 1} );
+                        count = 0;
 
-                },
+                        let map_pos = self.map_pos;
+                        self.add_action(Action::Teleport(map_pos));
 
-                Tile::Target => {
-                    self.add_action(Action::Move{ dir, steps: count + 1 });
-                    self.reached_target = true;
-                    break;
-                },
+                        // Some teleporters send the player off in a new direction
+                        if let Tile::Teleporter(_, Some(exit_dir)) = tile_map.get_tile_at(destination) {
+                            dir = exit_dir;
+                            unit_vec = dir.get_unit_vec();
+                        }
 
-                _=> {
-                    count += 1;
-                },
+                    },
+
+                    Tile::Deflector(orientation) => {
+                        tile_map.break_ice(from);
+                        self.add_action(Action::Move{ dir, steps: count + 1 });
+                        count = 0;
+
+                        // Coming back the same way would go round the same loop forever
+                        if visited.contains(&(self.map_pos, dir)) {
+                            break;
+                        }
+
+                        visited.push((self.map_pos, dir));
+
+                        dir = orientation.deflect(dir);
+                        unit_vec = dir.get_unit_vec();
+                    },
+
+                    Tile::Star => {
+                        self.stars += 1;
+
+                        tile_map.set_tile_at(self.map_pos, Tile::Empty);
+                        tile_map.break_ice(from);
+                        count += 1;
+                    },
+
+                    Tile::Key(color) => {
+                        self.keys.push(color);
 
+                        tile_map.set_tile_at(self.map_pos, Tile::Empty);
+                        tile_map.break_ice(from);
+                        count += 1;
+                    },
+
+                    Tile::Switch(id) => {
+                        count += 1;
+                        tile_map.break_ice(from);
+
+                        let occupied: Vec<Vector2i> = others.iter().cloned()
+                            .chain(blocks.iter().filter(|block| !block.fell()).map(Block::map_pos))
+                            .collect();
+                        tile_map.toggle_doors(id, &occupied);
+
+                        let frames = self.action_queue.iter().map(Action::frames).sum::<u32>() + Action::Move{ dir, steps: count }.frames();
+                        self.pressed_switches.push((id, frames));
+                    },
+
+                    Tile::Snow => {
+                        self.add_action(Action::Move{ dir, steps: count + 1 });
+                        tile_map.break_ice(from);
+                        break;
+                    },
+
+                    Tile::Hole => {
+                        self.add_action(Action::Move{ dir, steps: count + 1 });
+                        self.add_action(Action::Fall);
+                        self.fell = true;
+                        tile_map.break_ice(from);
+                        break;
+                    },
+
+                    Tile::Target | Tile::LitTarget => {
+                        self.add_action(Action::Move{ dir, steps: count + 1 });
+                        self.reached_target = true;
+                        tile_map.set_tile_at(self.map_pos, Tile::LitTarget);
+                        tile_map.break_ice(from);
+                        break;
+                    },
+
+                    _=> {
+                        count += 1;
+                        tile_map.break_ice(from);
+                    },
+
+                }
+
+            }
+
+            if self.reached_target || self.fell {
+                break;
+            }
+
+            // Coming to rest on a conveyor carries the player on, unless it has already carried
+            // them this move, which means they're going round in a loop.
+            match tile_map.get_tile_at(self.map_pos) {
+                Tile::Conveyor(conveyor_dir) if !conveyors.contains(&self.map_pos) => {
+                    conveyors.push(self.map_pos);
+
+                    dir = conveyor_dir;
+                    unit_vec = dir.get_unit_vec();
+                    count = 0;
+                    carried_from = Some(self.map_pos);
+                },
+                _ => break,
             }
 
         }
 
+        // Still counts if the player was already on a target and couldn't move off it
+        self.reached_target = !self.fell && tile_map.get_tile_at(self.map_pos).is_target();
+
+        pushed || !self.is_ready()
+
+    }
+
+    pub fn stars(&self) -> u32 {
+        self.stars
+    }
+
+    pub fn moves(&self) -> u32 {
+        self.moves
+    }
+
+    pub fn keys(&self) -> &[KeyColor] {
+        &self.keys
+    }
+
+    pub fn pressed_switches(&self) -> &[(u32, u32)] {
+        &self.pressed_switches
     }
 
     pub fn reached_target(&self) -> bool {
         self.is_ready() && self.reached_target
     }
 
+    // Only once the fall has finished
+    pub fn fell(&self) -> bool {
+        self.is_ready() && self.fell
+    }
+
+}
+
+// Slides every player in `dir` at once, front-most first so that the players behind stop against
+// where they end up. Returns whether any of them moved or pushed a block. Rotating one-ways turn
+// after every move.
+pub fn move_all(players: &mut [Player], dir: Direction, tile_map: &mut TileMap, blocks: &mut [Block]) -> bool {
+
+    let unit_vec = dir.get_unit_vec();
+
+    let mut order: Vec<usize> = (0..players.len()).collect();
+    order.sort_by_key(|&i| -(players[i].map_pos.x * unit_vec.x + players[i].map_pos.y * unit_vec.y));
+
+    let mut moved = false;
+
+    for &i in order.iter() {
+        let others: Vec<Vector2i> = players.iter().enumerate()
+            .filter(|&(j, player)| j != i && !player.fell)
+            .map(|(_, player)| player.map_pos)
+            .collect();
+
+        moved |= players[i].set_direction(dir, tile_map, blocks, &others);
+    }
+
+    if moved {
+        for player in players.iter_mut() {
+            player.moves += 1;
+        }
+
+        tile_map.turn_rotating();
+    }
+
+    moved
+
+}
+
+impl Action {
+
+    // How long the action takes to animate
+    pub fn frames(&self) -> u32 {
+        match *self {
+            Action::Move { steps, .. } => (steps as f32 * 65.0 / SPEED) as u32,
+            Action::Teleport(_) => WARP_FRAMES,
+            Action::Fall => FALL_FRAMES,
+            Action::Wait(frames) => frames,
+        }
+    }
+
+}
+
+// Plays one frame of the action at the front of the queue, removing it once it has finished.
+// Shared by everything which moves around the board.
+pub fn animate(action_queue: &mut VecDeque<Action>, action_counter: &mut u32, window_pos: &mut Vector2f, scale: &mut f32) {
+
+    if let Some(action) = action_queue.front() {
+
+        if *action_counter == 0 {
+            *action_counter = action.frames();
+        }
+
+        match *action {
+            Action::Move { dir, .. } => {
+                let unit_vec = dir.get_unit_vec();
+
+                window_pos.x += unit_vec.x as f32 * SPEED;
+                window_pos.y += unit_vec.y as f32 * SPEED;
+            },
+            Action::Teleport(pos) => {
+                let half = WARP_FRAMES / 2;
+
+                if *action_counter > half {
+                    // Shrink into the teleporter that was entered...
+                    *scale = (*action_counter - half - 1) as f32 / half as f32;
+                } else {
+                    // ...then grow out of the linked one.
+                    window_pos.x = pos.x as f32 * 65.0;
+                    window_pos.y = pos.y as f32 * 65.0;
+                    *scale = (half - *action_counter + 1) as f32 / half as f32;
+                }
+            },
+            Action::Fall => {
+                *scale = (*action_counter - 1) as f32 / FALL_FRAMES as f32;
+            },
+            Action::Wait(_) => {},
+        }
+
+        *action_counter -= 1;
+
+    }
+
+    if !action_queue.is_empty() && *action_counter == 0 {
+        action_queue.pop_front();
+    }
+
 }
@@ -0,0 +1,93 @@
+use level::{Direction, Tile, TileMap};
+use level::mesh;
+
+use transition::{Delay, Transition};
+
+use resources::Resources;
+use renderer::Renderer;
+
+use sfml::system::{Vector2, Vector2f, Vector2i};
+use sfml::graphics::{PrimitiveType, Transform, VertexArray};
+
+// The arrow on a rotating one-way. Which way it points is part of the TileMap, but the arrow turns
+// once everything has stopped moving rather than snapping round in the middle of a move.
+pub struct RotatingArrow {
+    pos: Vector2i,
+    dir: Direction, // Which way it's turning to, or pointing once it's still
+    rotation: Transition<f32>, // In degrees, clockwise from up
+}
+
+impl RotatingArrow {
+
+    const FRAMES: u32 = 12;
+
+    // Every rotating one-way in the map, pointing as they are now.
+    pub fn find_all(tile_map: &TileMap) -> Vec<RotatingArrow> {
+        let mut arrows = Vec::new();
+
+        for (y, row) in tile_map.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                if let Tile::Rotating(dir) = *tile {
+                    let rot = dir.get_rot() as f32;
+
+                    arrows.push(RotatingArrow {
+                        pos: Vector2::new(x as i32, y as i32),
+                        dir,
+                        rotation: Transition::new(rot, rot, 1, Delay::None),
+                    });
+                }
+            }
+        }
+
+        arrows
+    }
+
+    // Turns towards however the one-way points in `tile_map`, once `ready`.
+    pub fn update(&mut self, tile_map: &TileMap, ready: bool) {
+
+        self.rotation.update();
+
+        if !ready {
+            return;
+        }
+
+        if let Tile::Rotating(dir) = tile_map.get_tile_at(self.pos) {
+            if dir != self.dir {
+                // Always clockwise, carrying on from wherever the arrow is now
+                let from = self.rotation.get_val();
+                let mut to = dir.get_rot() as f32;
+
+                while to <= from {
+                    to += 360.0;
+                }
+
+                self.rotation = Transition::new(from, to, Self::FRAMES, Delay::None);
+                self.rotation.start();
+                self.dir = dir;
+            }
+        }
+
+    }
+
+    pub fn draw(&self, renderer: &mut Renderer, position: Vector2f, res: &Resources) {
+        let theme = res.theme();
+
+        let corner = Vector2::new(self.pos.x as f32 * 65.0, self.pos.y as f32 * 65.0);
+        let points = Tile::arrow_points(self.rotation.get_val(), corner);
+
+        let mut vertices = VertexArray::new(PrimitiveType::Triangles, 0);
+        mesh::push_triangle(&mut vertices, points, theme.oneway);
+
+        if res.settings.tile_shapes.get() {
+            for i in 0..3 {
+                mesh::push_line(&mut vertices, points[i], points[(i + 1) % 3], 2.0, theme.text);
+            }
+        }
+
+        let mut transform = Transform::IDENTITY;
+        transform.translate(position.x, position.y);
+
+        renderer.vertices(&vertices, transform, None);
+    }
+
+}
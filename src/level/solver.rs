@@ -0,0 +1,108 @@
+use std::collections::{HashSet, VecDeque};
+
+use level::{Direction, Goal, KeyColor, TileMap};
+use level::block::Block;
+use level::goal;
+use level::player::{self, Player};
+
+// Gives up rather than freezing the game on a level with a huge number of states.
+const MAX_STATES: usize = 100000;
+
+// The shortest list of moves which meets every goal, or None if there isn't one.
+pub fn solve(players: &[Player], tile_map: &TileMap, blocks: &[Block], goals: &[Goal]) -> Option<Vec<Direction>> {
+    search(players, tile_map, blocks, goals, |_tile_map| true)
+}
+
+// The shortest list of moves which collects every star on the way to meeting every goal.
+pub fn solve_all_stars(players: &[Player], tile_map: &TileMap, blocks: &[Block], goals: &[Goal]) -> Option<Vec<Direction>> {
+    search(players, tile_map, blocks, goals, |tile_map| tile_map.stars() == 0)
+}
+
+// Breadth-first search over every state the level can get into, which is where the players and
+// blocks are, which keys each player holds and how the tiles have changed. Moves which fall down a
+// hole or fail a goal are never part of a solution, and meeting every goal only counts if
+// `is_complete` agrees.
+fn search<F>(players: &[Player], tile_map: &TileMap, blocks: &[Block], goals: &[Goal], is_complete: F) -> Option<Vec<Direction>>
+    where F: Fn(&TileMap) -> bool {
+
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+
+    visited.insert(state_key(players, tile_map, blocks));
+    queue.push_back((players.to_vec(), tile_map.clone(), blocks.to_vec(), Vec::new()));
+
+    while let Some((players, tile_map, blocks, moves)) = queue.pop_front() {
+
+        for &dir in Direction::ALL.iter() {
+
+            let mut next_players = players.clone();
+            let mut next_map = tile_map.clone();
+            let mut next_blocks = blocks.clone();
+
+            if !player::move_all(&mut next_players, dir, &mut next_map, &mut next_blocks) {
+                continue;
+            }
+
+            for player in next_players.iter_mut() {
+                player.finish_actions();
+            }
+
+            for block in next_blocks.iter_mut() {
+                block.finish_actions();
+            }
+
+            if next_players.iter().any(Player::fell) || goal::any_failed(goals, &next_players, &next_map) {
+                continue;
+            }
+
+            let mut next_moves = moves.clone();
+            next_moves.push(dir);
+
+            if goal::all_met(goals, &next_players, &next_map) {
+                if is_complete(&next_map) {
+                    return Some(next_moves);
+                }
+
+                // The level ends as soon as every goal is met
+                continue;
+            }
+
+            if visited.len() >= MAX_STATES {
+                return None;
+            }
+
+            if visited.insert(state_key(&next_players, &next_map, &next_blocks)) {
+                queue.push_back((next_players, next_map, next_blocks, next_moves));
+            }
+
+        }
+
+    }
+
+    None
+
+}
+
+// Blocks are interchangeable, so only the set of places they're in matters. The same goes for keys
+// of the same colour.
+fn state_key(players: &[Player], tile_map: &TileMap, blocks: &[Block]) -> (Vec<((i32, i32), Vec<KeyColor>)>, TileMap, Vec<(i32, i32)>) {
+    let player_states = players.iter()
+        .map(|player| {
+            let pos = player.map_pos();
+
+            let mut keys = player.keys().to_vec();
+            keys.sort();
+
+            ((pos.x, pos.y), keys)
+        })
+        .collect();
+
+    let mut block_positions: Vec<(i32, i32)> = blocks.iter()
+        .filter(|block| !block.fell())
+        .map(|block| (block.map_pos().x, block.map_pos().y))
+        .collect();
+
+    block_positions.sort();
+
+    (player_states, tile_map.clone(), block_positions)
+}
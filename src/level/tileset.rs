@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use level::{Orientation, Tile};
+use level::mesh;
+
+use sfml::system::Vector2f;
+use sfml::graphics::{IntRect, Texture, TextureRef, VertexArray};
+
+// Sprites for tiles, cut from a single atlas image. The mapping file names the image and gives the
+// region of the atlas used for each kind of tile:
+//
+//     image         tileset.png
+//     wall          0 0 64 64
+//     teleporter    64 0 64 64
+//     teleporter_2  128 0 64 64
+//     oneway        192 0 64 64
+//
+// Regions are `x y width height` in pixels and are scaled to fill a tile. One-way tiles and
+// conveyors should be drawn pointing up, and are rotated for the other directions. Deflectors
+// should be drawn like `/`, and are turned a quarter for `\`. Rotating one-ways have their arrow
+// drawn over the top, so `rotating` should only be the tile underneath. `teleporter_N` gives the
+// sprite for the Nth pair, falling back to `teleporter`, `key_red`, `lock_red` and so on fall
+// back to `key` and `lock`, and `lit_target` falls back to `target`. Tiles without a region are
+// drawn procedurally.
+pub struct Tileset {
+    texture: Texture,
+    regions: HashMap<String, IntRect>,
+}
+
+impl Tileset {
+
+    pub const FILE: &'static str = "res/tileset.cfg";
+
+    // None if no tileset has been configured.
+    pub fn from_file(path: &str) -> Option<Tileset> {
+        let file = File::open(path).ok()?;
+
+        let mut image = None;
+        let mut regions = HashMap::new();
+
+        for (line_num, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.expect("Could not read tileset file!");
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            if fields[0] == "image" && fields.len() == 2 {
+                image = Some(fields[1].to_string());
+                continue;
+            }
+
+            let region = Self::parse_region(&fields[1..])
+                .unwrap_or_else(|| panic!("Invalid region on line {} of {}", line_num + 1, path));
+
+            regions.insert(fields[0].to_string(), region);
+        }
+
+        // The image is relative to the mapping file.
+        let image = image.unwrap_or_else(|| panic!("No image given in {}", path));
+        let image_path = Path::new(path).with_file_name(image);
+
+        let texture = Texture::from_file(image_path.to_str().unwrap())
+            .unwrap_or_else(|| panic!("Could not load tileset image {}", image_path.display()));
+
+        Some(Tileset {
+            texture,
+            regions,
+        })
+    }
+
+    fn parse_region(fields: &[&str]) -> Option<IntRect> {
+        if fields.len() != 4 {
+            return None;
+        }
+
+        Some(IntRect::new(
+            fields[0].parse().ok()?,
+            fields[1].parse().ok()?,
+            fields[2].parse().ok()?,
+            fields[3].parse().ok()?,
+        ))
+    }
+
+    fn region(&self, tile: &Tile) -> Option<&IntRect> {
+        match *tile {
+            Tile::Empty => self.regions.get("empty"),
+            Tile::Start => self.regions.get("start"),
+            Tile::Wall => self.regions.get("wall"),
+            Tile::Target => self.regions.get("target"),
+            Tile::LitTarget => self.regions.get("lit_target").or_else(|| self.regions.get("target")),
+            Tile::Cracked => self.regions.get("cracked"),
+            Tile::Snow => self.regions.get("snow"),
+            Tile::Hole => self.regions.get("hole"),
+            Tile::Switch(_) => self.regions.get("switch"),
+            Tile::Star => self.regions.get("star"),
+            Tile::Door(..) => None, // Drawn by the level, as they open and shut
+            Tile::Key(color) => self.regions.get(&format!("key_{}", color.name()))
+                .or_else(|| self.regions.get("key")),
+            Tile::Lock(color) => self.regions.get(&format!("lock_{}", color.name()))
+                .or_else(|| self.regions.get("lock")),
+            Tile::OneWay(_) => self.regions.get("oneway"),
+            Tile::Deflector(_) => self.regions.get("deflector"),
+            Tile::Conveyor(_) => self.regions.get("conveyor"),
+            Tile::Rotating(_) => self.regions.get("rotating"),
+            Tile::Teleporter(index, _) => self.regions.get(&format!("teleporter_{}", index + 1))
+                .or_else(|| self.regions.get("teleporter")),
+        }
+    }
+
+    pub fn texture(&self) -> &TextureRef {
+        &self.texture
+    }
+
+    // Returns false if the tile has no sprite, so should be drawn procedurally.
+    pub fn build_tile(&self, vertices: &mut VertexArray, tile: &Tile, position: Vector2f) -> bool {
+
+        let region = match self.region(tile) {
+            Some(region) => region,
+            None => return false,
+        };
+
+        let rot = match *tile {
+            Tile::OneWay(dir) | Tile::Conveyor(dir) => dir.get_rot() as f32,
+            Tile::Deflector(Orientation::Falling) => 90.0,
+            _ => 0.0,
+        };
+
+        mesh::push_sprite(vertices, position, region, rot);
+
+        true
+    }
+
+}
@@ -2,8 +2,7 @@ extern crate sfml;
 
 use sfml::system::Vector2f;
 use sfml::window::{ContextSettings, VideoMode, Event, Key, Style};
-use sfml::graphics::{CircleShape, Color, RenderTarget, RenderWindow, Shape, Transformable};
-use sfml::graphics::{Font, Text};
+use sfml::graphics::{CircleShape, RenderWindow, Shape};
 
 mod scene;
 use scene::{SceneManager, Sceneable, SceneAction, Scene};
@@ -19,6 +18,25 @@ mod level;
 mod transition;
 use transition::{Transition, Delay};
 
+mod typography;
+use typography::{Label, TextStyle};
+
+mod theme;
+
+mod settings;
+
+mod accessibility;
+
+mod renderer;
+use renderer::Renderer;
+
+#[cfg(test)]
+mod snapshot;
+
+mod save;
+
+mod challenge;
+
 struct IntArray<const WIDTH: usize> {
     array: [i32; WIDTH],
 }
@@ -27,9 +45,6 @@ struct IntArray<const WIDTH: usize> {
 const WINDOW_WIDTH: u32 = 960;
 const WINDOW_HEIGHT: u32 = 640;
 
-const BG_COLOR: Color = Color { r: 240, g: 240, b: 240, a: 255 };
-const TEXT_COLOR: Color = Color { r: 50, g: 50, b: 50, a: 255 };
-
 fn main() {
 
     let mut window = RenderWindow::new(VideoMode::new(WINDOW_WIDTH, WINDOW_HEIGHT, 32), "Ice Puzzle Game", Style::CLOSE, &ContextSettings::default());
@@ -48,13 +63,13 @@ fn main() {
         }
 
         scene_manager.update(&resources);
-        scene_manager.draw(&mut window);
+        scene_manager.draw(&mut window, &resources);
     }
 
 }
 
 struct SplashScene<'a> {
-    text: Text<'a>,
+    text: Label<'a>,
     trans: Transition<u8>,
 }
 
@@ -62,10 +77,9 @@ impl<'a> SplashScene<'a> {
     fn new(resources: &Resources) -> Box<SplashScene> {
         Box::new(SplashScene {
             text: {
-                let mut t = Text::new("Ice Puzzle", &resources.menu_res.raleway, 50);
+                let mut t = Label::new("Ice Puzzle", TextStyle::Title, resources);
                 let text_bounds = t.local_bounds();
                 t.set_position(( (WINDOW_WIDTH as f32 - text_bounds.width) / 2.0, (WINDOW_HEIGHT as f32 - text_bounds.height - 50.0) / 2.0));
-                t.set_fill_color(&TEXT_COLOR);
                 t
             },
             trans: Transition::new(255, 0, 60, Delay::Post(60)),
@@ -75,7 +89,7 @@ impl<'a> SplashScene<'a> {
 
 impl<'a> Sceneable for SplashScene<'a> {
     fn update(&mut self, _res: &Resources) -> SceneAction {
-        let mut color = TEXT_COLOR;
+        let mut color = self.text.fill_color();
         color.a = self.trans.get_val();
         self.text.set_fill_color(&color);
 
@@ -86,9 +100,9 @@ impl<'a> Sceneable for SplashScene<'a> {
         }
     }
 
-    fn draw(&self, window: &mut RenderWindow) {
-        window.clear(&BG_COLOR);
-        window.draw(&self.text);
+    fn draw(&self, renderer: &mut Renderer, resources: &Resources) {
+        renderer.fill(&resources.theme().background);
+        self.text.draw(renderer);
     }
 
     fn handle_event(&mut self, event: Event, resources: &Resources) -> SceneAction {
@@ -1,5 +1,6 @@
 use std::cmp;
-use ::{WINDOW_HEIGHT, WINDOW_WIDTH, BG_COLOR, TEXT_COLOR};
+use std::ops::Range;
+use ::{WINDOW_HEIGHT, WINDOW_WIDTH};
 
 use resources::Resources;
 
@@ -7,46 +8,61 @@ use scene::{SceneAction, Sceneable, Scene};
 
 use transition::{Transition, Delay};
 
+use typography::{Label, TextStyle};
+
+use renderer::Renderer;
+
+use level::mesh;
+
 use sfml::window::{Event, Key};
-use sfml::graphics::{Text, Color, RenderWindow, RenderTarget, Transformable};
+use sfml::system::{Vector2, Vector2f};
+use sfml::graphics::{PrimitiveType, RectangleShape, Shape, Transform, Transformable, VertexArray};
 
 //use level::Level;
 
 // Play
+// Settings
+// Editor
 // Credits
 // Exit
 
 pub struct MainMenu<'a> {
-    title_text: Text<'a>,
-    menu_items: [MenuItem<'a>; 3],
+    title_text: Label<'a>,
+    menu_items: [MenuItem<'a>; 5],
     current_item: usize,
     trans: Transition<u8>,
 }
 
 impl<'a> MainMenu<'a> {
 
-    const SELECTED_COLOR: Color = Color { r: 195, g: 77, b: 88, a: 255 };
-
     pub fn new(res: &Resources) -> Box<MainMenu> {
-        let title_text = Text::new("Ice Game", &res.menu_res.raleway, 36);
+        let title_text = Label::new("Ice Game", TextStyle::Title, res);
 
         let play_text = MenuItem::new(
-            Text::new("Play", &res.menu_res.raleway, 28),
-            SceneAction::Change(Scene::Level(0))
+            Label::new("Play", TextStyle::MenuItem, res),
+            SceneAction::Push(Scene::PackSelect)
+        );
+        let settings_text = MenuItem::new(
+            Label::new("Settings", TextStyle::MenuItem, res),
+            SceneAction::Push(Scene::Settings)
+        );
+        let editor_text = MenuItem::new(
+            Label::new("Editor", TextStyle::MenuItem, res),
+            SceneAction::Push(Scene::Editor)
         );
         let credits_text = MenuItem::new(
-            Text::new("Credits", &res.menu_res.raleway, 28),
+            Label::new("Credits", TextStyle::MenuItem, res),
             SceneAction::NoChange
         );
         let exit_text = MenuItem::new(
-            Text::new("Quit", &res.menu_res.raleway, 28),
+            Label::new("Quit", TextStyle::MenuItem, res),
             SceneAction::Quit
         );
         let trans = Transition::new(0, 255, 60, Delay::None);
         
         let mut menu: Box<MainMenu> = Box::new(MainMenu {
             title_text,
-            menu_items: [play_text, credits_text, exit_text],
+            menu_items: [play_text, settings_text, editor_text, credits_text, exit_text],
             current_item: 0,
             trans,
         });
@@ -54,39 +70,28 @@ impl<'a> MainMenu<'a> {
         menu.trans.start();
 
         let title_width = menu.title_text.local_bounds().width;
-        menu.title_text.set_fill_color(&TEXT_COLOR);
         menu.title_text.set_position(( (WINDOW_WIDTH as f32 - title_width) / 2.0, 100.0));
 
         for (index, menu_item) in menu.menu_items.iter_mut().enumerate() {
-            menu_item.text.set_position((350.0, 240.0 + (index * 100) as f32));
+            menu_item.text.set_position((350.0, 220.0 + (index * 72) as f32));
         }
 
-        menu.update_colors();
+        menu.update_colors(res);
 
         menu
     }
 
-    fn update_colors(&mut self) {
-        for (index, menu_item) in self.menu_items.iter_mut().enumerate() {
-            if index == self.current_item {
-                let mut color = menu_item.text.fill_color();
-                color.r = Self::SELECTED_COLOR.r;
-                color.g = Self::SELECTED_COLOR.g;
-                color.b = Self::SELECTED_COLOR.b;
-                menu_item.text.set_fill_color(&color);
-            } else {
-                let mut color = menu_item.text.fill_color();
-                color.r = TEXT_COLOR.r;
-                color.g = TEXT_COLOR.g;
-                color.b = TEXT_COLOR.b;
-                menu_item.text.set_fill_color(&color);
-            }
-        }
+    fn update_colors(&mut self, res: &Resources) {
+        self.title_text.refresh_color(res);
+        highlight_selected(&mut self.menu_items, self.current_item, res);
     }
 }
 
 impl<'a> Sceneable for MainMenu<'a> {
-    fn update(&mut self, _res: &Resources) -> SceneAction {
+    fn update(&mut self, res: &Resources) -> SceneAction {
+        // The theme may have been changed from the settings menu.
+        self.update_colors(res);
+
         let alpha = self.trans.get_val();
 
         let mut color = self.title_text.fill_color();
@@ -104,18 +109,18 @@ impl<'a> Sceneable for MainMenu<'a> {
         SceneAction::NoChange
     }
 
-    fn draw(&self, window: &mut RenderWindow) {
-        window.clear(&BG_COLOR);
+    fn draw(&self, renderer: &mut Renderer, res: &Resources) {
+        renderer.fill(&res.theme().background);
 
-        window.draw(&self.title_text);
+        self.title_text.draw(renderer);
 
         for menu_item in self.menu_items.iter() {
-            window.draw(&menu_item.text);
+            menu_item.text.draw(renderer);
         }
 
     }
 
-    fn handle_event(&mut self, event: Event, res: &Resources) -> SceneAction {
+    fn handle_event(&mut self, event: Event, _res: &Resources) -> SceneAction {
 
         if let Event::KeyPressed { code, .. } = event{
             match code {
@@ -123,14 +128,10 @@ impl<'a> Sceneable for MainMenu<'a> {
                     if self.current_item != 0 {
                         self.current_item -= 1;
                     }
-
-                    self.update_colors();
                 },
 
                 Key::Down => {
                     self.current_item = cmp::min(self.current_item + 1, self.menu_items.len() - 1);
-
-                    self.update_colors();
                 },
 
                 Key::Return => {
@@ -146,12 +147,12 @@ impl<'a> Sceneable for MainMenu<'a> {
 
 
 struct MenuItem<'a> {
-    text: Text<'a>,
+    text: Label<'a>,
     target_scene: SceneAction,
 }
 
 impl<'a> MenuItem<'a> {
-    fn new(text: Text, target_scene: SceneAction) -> MenuItem {
+    fn new(text: Label, target_scene: SceneAction) -> MenuItem {
         MenuItem {
             text,
             target_scene,
@@ -159,51 +160,521 @@ impl<'a> MenuItem<'a> {
     }
 }
 
-/*struct LevelSelect {
-    font: Font,
+// Colours the selected item with the theme's accent, and the rest with their style's colour.
+fn highlight_selected(menu_items: &mut [MenuItem], current_item: usize, res: &Resources) {
+    for (index, menu_item) in menu_items.iter_mut().enumerate() {
+        menu_item.text.refresh_color(res);
+
+        if index == current_item {
+            let mut color = res.theme().accent;
+            color.a = menu_item.text.fill_color().a;
+            menu_item.text.set_fill_color(&color);
+        }
+    }
+}
+
+fn on_off(setting: bool) -> &'static str {
+    if setting { "On" } else { "Off" }
+}
+
+// Theme
+// Tile shapes
+// Colour vision
+// Teleporter links
+// Auto restart
+// Challenge
+// HUD
+// Back
+
+pub struct SettingsMenu<'a> {
+    title_text: Label<'a>,
+    menu_items: [MenuItem<'a>; 8],
+    current_item: usize,
+}
+
+impl<'a> SettingsMenu<'a> {
+
+    const THEME_ITEM: usize = 0;
+    const TILE_SHAPES_ITEM: usize = 1;
+    const COLOR_VISION_ITEM: usize = 2;
+    const TELEPORTER_LINKS_ITEM: usize = 3;
+    const AUTO_RESTART_ITEM: usize = 4;
+    const CHALLENGE_ITEM: usize = 5;
+    const HUD_ITEM: usize = 6;
+
+    pub fn new(res: &Resources) -> Box<SettingsMenu> {
+        let title_text = Label::new("Settings", TextStyle::Title, res);
+
+        let theme_text = MenuItem::new(
+            Label::new("", TextStyle::MenuItem, res),
+            SceneAction::NoChange
+        );
+        let tile_shapes_text = MenuItem::new(
+            Label::new("", TextStyle::MenuItem, res),
+            SceneAction::NoChange
+        );
+        let color_vision_text = MenuItem::new(
+            Label::new("", TextStyle::MenuItem, res),
+            SceneAction::NoChange
+        );
+        let teleporter_links_text = MenuItem::new(
+            Label::new("", TextStyle::MenuItem, res),
+            SceneAction::NoChange
+        );
+        let auto_restart_text = MenuItem::new(
+            Label::new("", TextStyle::MenuItem, res),
+            SceneAction::NoChange
+        );
+        let challenge_text = MenuItem::new(
+            Label::new("", TextStyle::MenuItem, res),
+            SceneAction::NoChange
+        );
+        let hud_text = MenuItem::new(
+            Label::new("", TextStyle::MenuItem, res),
+            SceneAction::NoChange
+        );
+        let back_text = MenuItem::new(
+            Label::new("Back", TextStyle::MenuItem, res),
+            SceneAction::Pop(1)
+        );
+
+        let mut menu: Box<SettingsMenu> = Box::new(SettingsMenu {
+            title_text,
+            menu_items: [theme_text, tile_shapes_text, color_vision_text, teleporter_links_text, auto_restart_text, challenge_text, hud_text, back_text],
+            current_item: 0,
+        });
+
+        let title_width = menu.title_text.local_bounds().width;
+        menu.title_text.set_position(( (WINDOW_WIDTH as f32 - title_width) / 2.0, 100.0));
+
+        for (index, menu_item) in menu.menu_items.iter_mut().enumerate() {
+            menu_item.text.set_position((350.0, 180.0 + (index * 52) as f32));
+        }
+
+        menu.update_text(res);
+
+        menu
+    }
+
+    fn update_text(&mut self, res: &Resources) {
+
+        self.menu_items[Self::THEME_ITEM].text.set_string(&format!("Theme: {}", res.theme().name));
+        self.menu_items[Self::TILE_SHAPES_ITEM].text.set_string(&format!("Tile shapes: {}", on_off(res.settings.tile_shapes.get())));
+        self.menu_items[Self::COLOR_VISION_ITEM].text.set_string(&format!("Colour vision: {}", res.settings.color_vision.get().name()));
+        self.menu_items[Self::TELEPORTER_LINKS_ITEM].text.set_string(&format!("Teleporter links: {}", on_off(res.settings.teleporter_links.get())));
+        self.menu_items[Self::AUTO_RESTART_ITEM].text.set_string(&format!("Auto restart: {}", on_off(res.settings.auto_restart.get())));
+        self.menu_items[Self::CHALLENGE_ITEM].text.set_string(&format!("Challenge: {}", res.settings.challenge.get().name()));
+        self.menu_items[Self::HUD_ITEM].text.set_string(&format!("HUD: {}", on_off(res.settings.show_hud.get())));
+
+        self.title_text.refresh_color(res);
+        highlight_selected(&mut self.menu_items, self.current_item, res);
+    }
+
+    // Left and right (or return) cycle the value of the selected setting.
+    fn change_setting(&mut self, res: &Resources) {
+        match self.current_item {
+            Self::THEME_ITEM => res.next_theme(),
+            Self::TILE_SHAPES_ITEM => res.settings.tile_shapes.set(!res.settings.tile_shapes.get()),
+            Self::COLOR_VISION_ITEM => res.next_color_vision(),
+            Self::TELEPORTER_LINKS_ITEM => res.settings.teleporter_links.set(!res.settings.teleporter_links.get()),
+            Self::AUTO_RESTART_ITEM => res.settings.auto_restart.set(!res.settings.auto_restart.get()),
+            Self::CHALLENGE_ITEM => res.settings.challenge.set(res.settings.challenge.get().next()),
+            Self::HUD_ITEM => res.settings.show_hud.set(!res.settings.show_hud.get()),
+            _ => {},
+        }
+
+        self.update_text(res);
+    }
+}
+
+impl<'a> Sceneable for SettingsMenu<'a> {
+    fn update(&mut self, _res: &Resources) -> SceneAction {
+        SceneAction::NoChange
+    }
+
+    fn draw(&self, renderer: &mut Renderer, res: &Resources) {
+        renderer.fill(&res.theme().background);
+
+        self.title_text.draw(renderer);
+
+        for menu_item in self.menu_items.iter() {
+            menu_item.text.draw(renderer);
+        }
+    }
+
+    fn handle_event(&mut self, event: Event, res: &Resources) -> SceneAction {
+
+        if let Event::KeyPressed { code, .. } = event {
+            match code {
+                Key::Up => {
+                    if self.current_item != 0 {
+                        self.current_item -= 1;
+                    }
+
+                    self.update_text(res);
+                },
+
+                Key::Down => {
+                    self.current_item = cmp::min(self.current_item + 1, self.menu_items.len() - 1);
+
+                    self.update_text(res);
+                },
+
+                Key::Left | Key::Right => self.change_setting(res),
+
+                Key::Return => {
+                    let target_scene = self.menu_items[self.current_item].target_scene.clone();
+
+                    if let SceneAction::NoChange = target_scene {
+                        self.change_setting(res);
+                    }
+
+                    return target_scene;
+                },
+
+                Key::Escape => return SceneAction::Pop(1),
+
+                _ => {},
+            }
+        }
+        SceneAction::NoChange
+    }
 }
 
-impl LevelSelect {
-    const BUTTON_SIZE: f32 = WINDOW_HEIGHT as f32 / 4.0;
-    const BUTTON_COLOR: Color = Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 };
-    const BG_COLOR: Color = Color { r: 0.95, g: 0.95, b: 0.95, a: 0.95 };
+// A list of every level pack, showing how much of each has been finished, or what has to be done
+// to unlock it.
+pub struct PackSelect<'a> {
+    title_text: Label<'a>,
+    titles: Vec<Label<'a>>,
+    details: Vec<Label<'a>>,
+    current_pack: usize,
 }
 
-impl State for LevelSelect {
+impl<'a> PackSelect<'a> {
+
+    const LEFT: f32 = 200.0;
+    const TOP: f32 = 200.0;
+    const ROW_HEIGHT: f32 = 100.0;
+    const VISIBLE_ROWS: usize = 4;
+
+    pub fn new(res: &Resources) -> Box<PackSelect> {
+        let mut title_text = Label::new("Select Pack", TextStyle::Title, res);
+
+        let title_width = title_text.local_bounds().width;
+        title_text.set_position(( (WINDOW_WIDTH as f32 - title_width) / 2.0, 80.0));
+
+        Box::new(PackSelect {
+            title_text,
+            titles: res.packs.iter().map(|pack| Label::new(&pack.title, TextStyle::MenuItem, res)).collect(),
+            details: res.packs.iter().map(|_| Label::new("", TextStyle::Caption, res)).collect(),
+            current_pack: 0,
+        })
+    }
+
+    // The list scrolls to keep the selected pack in view.
+    fn first_row(&self) -> usize {
+        (self.current_pack + 1).saturating_sub(Self::VISIBLE_ROWS)
+    }
+
+    fn detail(pack_id: usize, res: &Resources) -> String {
+        let pack = &res.packs[pack_id];
+        let save = res.save.borrow();
+
+        if !pack.is_unlocked(&res.packs, &save) {
+            return pack.unlock_hint(&res.packs);
+        }
+
+        let mut parts = Vec::new();
+
+        if let Some(ref author) = pack.author {
+            parts.push(format!("by {}", author));
+        }
+
+        if pack.user {
+            parts.push("your own pack".to_string());
+        }
+
+        parts.push(format!("{} of {} levels finished", pack.completed(&save), pack.levels.len()));
+        parts.push(format!("{} of {} stars", pack.rating(&save), pack.levels.len() * 3));
 
-    fn update(&mut self) -> StateAction {
-        StateAction::NoChange
+        parts.join("   ")
     }
+}
 
-    fn draw(&self, ctx: &mut Context) {
-        graphics::set_background_color(ctx, Self::BG_COLOR);
-        graphics::clear(ctx);
+impl<'a> Sceneable for PackSelect<'a> {
+    // Progress is worked out again every frame, as levels may have been finished since.
+    fn update(&mut self, res: &Resources) -> SceneAction {
+        self.title_text.refresh_color(res);
 
-        use ::COLOR_SCHEME;
+        let first_row = self.first_row();
 
-        const WIN_HEIGHTF: f32 = WINDOW_HEIGHT as f32;
-        const WIN_WIDTHF: f32 = WINDOW_WIDTH as f32;
+        for pack_id in 0..res.packs.len() {
+            let top = Self::TOP + (pack_id as f32 - first_row as f32) * Self::ROW_HEIGHT;
+            let unlocked = res.packs[pack_id].is_unlocked(&res.packs, &res.save.borrow());
 
-        for level_id in 0..(::level::TileMap::MAPS.len()) {
-            let rect = graphics::Rect::new((Self::BUTTON_SIZE / 2.0) + (Self::BUTTON_SIZE * 1.5 * level_id as f32), (WIN_HEIGHTF - Self::BUTTON_SIZE) / 2.0, Self::BUTTON_SIZE, Self::BUTTON_SIZE);
-            graphics::set_color(ctx, COLOR_SCHEME[level_id % COLOR_SCHEME.len()]);
-            graphics::rectangle(ctx, graphics::DrawMode::Fill, rect);
+            let title = &mut self.titles[pack_id];
+            title.set_position((Self::LEFT, top));
+            title.refresh_color(res);
 
-            let text = Text::new(ctx, &level_id.to_string(), &self.font).unwrap();
-            graphics::set_color(ctx, MainMenu::TEXT_COLOR);
-            graphics::draw(ctx, &text, Point2::new(rect.x + (rect.w / 2.0), rect.y + (rect.h / 2.0)), 0.0);
+            if pack_id == self.current_pack {
+                title.set_fill_color(&res.theme().accent);
+            } else if !unlocked {
+                title.set_fill_color(&res.theme().muted_text);
+            }
+
+            let detail = &mut self.details[pack_id];
+            detail.set_string(&Self::detail(pack_id, res));
+            detail.set_position((Self::LEFT, top + 40.0));
+            detail.refresh_color(res);
         }
+
+        SceneAction::NoChange
     }
 
-    fn handle_input(&mut self, ctx: &mut Context, input_event: InputEvent) -> StateAction {
-        if let InputEvent::KeyDown(key) = input_event {
-            match key {
-                Keycode::Return => StateAction::Change(Box::new(::level::Level::new(0))),
-                _ => StateAction::NoChange,
+    fn draw(&self, renderer: &mut Renderer, res: &Resources) {
+        renderer.fill(&res.theme().background);
+
+        self.title_text.draw(renderer);
+
+        for pack_id in (self.first_row()..self.titles.len()).take(Self::VISIBLE_ROWS) {
+            self.titles[pack_id].draw(renderer);
+            self.details[pack_id].draw(renderer);
+        }
+    }
+
+    fn handle_event(&mut self, event: Event, res: &Resources) -> SceneAction {
+
+        if let Event::KeyPressed { code, .. } = event {
+            match code {
+                Key::Up => {
+                    if self.current_pack != 0 {
+                        self.current_pack -= 1;
+                    }
+                },
+
+                Key::Down => {
+                    self.current_pack = cmp::min(self.current_pack + 1, res.packs.len() - 1);
+                },
+
+                Key::Return => {
+                    if res.packs[self.current_pack].is_unlocked(&res.packs, &res.save.borrow()) {
+                        return SceneAction::Push(Scene::LevelSelect(self.current_pack));
+                    }
+                },
+
+                Key::Escape => return SceneAction::Pop(1),
+
+                _ => {},
             }
+        }
+        SceneAction::NoChange
+    }
+}
+
+// A grid of every level in a pack, showing the rating each has been completed with, and the title,
+// author and difficulty of the selected level underneath.
+pub struct LevelSelect<'a> {
+    title_text: Label<'a>,
+    numbers: Vec<Label<'a>>,
+    info_text: Label<'a>,
+    pack_id: usize,
+    current_level: usize,
+}
+
+impl<'a> LevelSelect<'a> {
+
+    const COLUMNS: usize = 6;
+    const BUTTON_SIZE: f32 = 100.0;
+    const BUTTON_GAP: f32 = 30.0;
+    const TOP: f32 = 200.0;
+    const VISIBLE_ROWS: usize = 3; // Above the info text
+    const INFO_TOP: f32 = 585.0;
+
+    pub fn new(res: &Resources, pack_id: usize) -> Box<LevelSelect> {
+        let pack = &res.packs[pack_id];
+        let mut title_text = Label::new(&pack.title, TextStyle::Title, res);
+
+        let title_width = title_text.local_bounds().width;
+        title_text.set_position(( (WINDOW_WIDTH as f32 - title_width) / 2.0, 80.0));
+
+        let numbers = (0..pack.levels.len())
+            .map(|level_id| Label::new(&(level_id + 1).to_string(), TextStyle::MenuItem, res))
+            .collect();
+
+        let mut menu = Box::new(LevelSelect {
+            title_text,
+            numbers,
+            info_text: Label::new("", TextStyle::Body, res),
+            pack_id,
+            current_level: 0,
+        });
+
+        menu.update_info(res);
+
+        menu
+    }
+
+    fn update_info(&mut self, res: &Resources) {
+        let pack = &res.packs[self.pack_id];
+
+        let mut info = if pack.is_level_unlocked(self.current_level, &res.save.borrow()) {
+            pack.levels[self.current_level].info.summary()
         } else {
-            StateAction::NoChange
+            format!("Finish level {} to unlock", self.current_level)
+        };
+
+        // The player's own levels can be opened in the editor, whether or not they're unlocked
+        if pack.user {
+            if !info.is_empty() {
+                info.push_str("   ");
+            }
+
+            info.push_str("E edit");
+        }
+
+        self.info_text.set_string(&info);
+
+        let info_width = self.info_text.local_bounds().width;
+        self.info_text.set_position(((WINDOW_WIDTH as f32 - info_width) / 2.0, Self::INFO_TOP));
+    }
+
+    // The grid scrolls to keep the selected level in view.
+    fn first_row(&self) -> usize {
+        (self.current_level / Self::COLUMNS + 1).saturating_sub(Self::VISIBLE_ROWS)
+    }
+
+    // The levels on the rows in view
+    fn visible_levels(&self) -> Range<usize> {
+        let first_level = self.first_row() * Self::COLUMNS;
+        first_level..cmp::min(first_level + Self::VISIBLE_ROWS * Self::COLUMNS, self.numbers.len())
+    }
+
+    fn button_position(&self, level_id: usize) -> Vector2f {
+        let columns = Self::COLUMNS as f32;
+        let left = (WINDOW_WIDTH as f32 - columns * Self::BUTTON_SIZE - (columns - 1.0) * Self::BUTTON_GAP) / 2.0;
+        let row = level_id / Self::COLUMNS - self.first_row();
+
+        Vector2::new(
+            left + (level_id % Self::COLUMNS) as f32 * (Self::BUTTON_SIZE + Self::BUTTON_GAP),
+            Self::TOP + row as f32 * (Self::BUTTON_SIZE + Self::BUTTON_GAP),
+        )
+    }
+}
+
+impl<'a> Sceneable for LevelSelect<'a> {
+    // The info is worked out again every frame, as the selected level may have been finished, or
+    // the next one unlocked, since.
+    fn update(&mut self, res: &Resources) -> SceneAction {
+        self.update_info(res);
+        self.title_text.refresh_color(res);
+        self.info_text.refresh_color(res);
+
+        let pack = &res.packs[self.pack_id];
+
+        for level_id in self.visible_levels() {
+            let corner = self.button_position(level_id);
+
+            let number = &mut self.numbers[level_id];
+            let bounds = number.local_bounds();
+
+            // Centred in the top part of the button, above the stars
+            number.set_position((
+                corner.x + (Self::BUTTON_SIZE - bounds.width) / 2.0 - bounds.left,
+                corner.y + 40.0 - bounds.top - bounds.height / 2.0,
+            ));
+
+            number.refresh_color(res);
+
+            if level_id == self.current_level {
+                number.set_fill_color(&res.theme().accent);
+            } else if !pack.is_level_unlocked(level_id, &res.save.borrow()) {
+                number.set_fill_color(&res.theme().muted_text);
+            }
         }
+
+        SceneAction::NoChange
     }
 
-}*/
+    fn draw(&self, renderer: &mut Renderer, res: &Resources) {
+        let theme = res.theme();
+        let save = res.save.borrow();
+        let pack = &res.packs[self.pack_id];
+
+        renderer.fill(&theme.background);
+
+        self.title_text.draw(renderer);
+
+        let mut button = RectangleShape::with_size(Vector2::new(Self::BUTTON_SIZE, Self::BUTTON_SIZE));
+        button.set_fill_color(&theme.board);
+        button.set_outline_thickness(3.0);
+
+        let mut stars = VertexArray::new(PrimitiveType::Triangles, 0);
+
+        for level_id in self.visible_levels() {
+            let corner = self.button_position(level_id);
+
+            let outline = if level_id == self.current_level { theme.accent } else { theme.grid_line };
+            button.set_outline_color(&outline);
+            button.set_position(corner);
+
+            renderer.rectangle(&button);
+            self.numbers[level_id].draw(renderer);
+
+            let rating = save.record(&pack.id, &pack.levels[level_id].name).map_or(0, |record| record.rating);
+
+            for star in 0..3 {
+                let centre = Vector2::new(corner.x + Self::BUTTON_SIZE / 2.0 + (star as f32 - 1.0) * 26.0, corner.y + 76.0);
+                let color = if star < rating { theme.star } else { theme.muted_text };
+
+                mesh::push_star(&mut stars, centre, 11.0, 4.5, color);
+            }
+        }
+
+        renderer.vertices(&stars, Transform::IDENTITY, None);
+
+        self.info_text.draw(renderer);
+    }
+
+    fn handle_event(&mut self, event: Event, res: &Resources) -> SceneAction {
+
+        if let Event::KeyPressed { code, .. } = event {
+            let pack = &res.packs[self.pack_id];
+            let last_level = pack.levels.len() - 1;
+
+            match code {
+                Key::Left => {
+                    if self.current_level != 0 {
+                        self.current_level -= 1;
+                    }
+                },
+
+                Key::Right => {
+                    self.current_level = cmp::min(self.current_level + 1, last_level);
+                },
+
+                Key::Up => {
+                    if self.current_level >= Self::COLUMNS {
+                        self.current_level -= Self::COLUMNS;
+                    }
+                },
+
+                Key::Down => {
+                    self.current_level = cmp::min(self.current_level + Self::COLUMNS, last_level);
+                },
+
+                Key::Return => {
+                    if pack.is_level_unlocked(self.current_level, &res.save.borrow()) {
+                        return SceneAction::Push(Scene::Level(self.pack_id, self.current_level));
+                    }
+                },
+
+                Key::E if pack.user => return SceneAction::Push(Scene::EditLevel(self.pack_id, self.current_level)),
+
+                Key::Escape => return SceneAction::Pop(1),
+
+                _ => {},
+            }
+        }
+        SceneAction::NoChange
+    }
+}
@@ -0,0 +1,178 @@
+#[cfg(test)]
+use std::fmt;
+
+use sfml::graphics::{BlendMode, Color, RectangleShape, RenderStates, RenderTarget, RenderWindow, Text, TextureRef, Transform, VertexArray};
+#[cfg(test)]
+use sfml::graphics::{FloatRect, Shape, Transformable};
+
+// Everything is drawn through a Renderer rather than straight to the window, so that scenes can
+// also be drawn without a GPU (see RecordingRenderer, which is only built for the tests).
+pub trait Renderer {
+    fn fill(&mut self, color: &Color); // Clears the whole target
+    fn rectangle(&mut self, rect: &RectangleShape);
+    fn text(&mut self, text: &Text);
+    fn vertices(&mut self, vertices: &VertexArray, transform: Transform, texture: Option<&TextureRef>);
+    fn present(&mut self);
+}
+
+impl Renderer for RenderWindow {
+
+    fn fill(&mut self, color: &Color) {
+        self.clear(color);
+    }
+
+    fn rectangle(&mut self, rect: &RectangleShape) {
+        self.draw(rect);
+    }
+
+    fn text(&mut self, text: &Text) {
+        self.draw(text);
+    }
+
+    fn vertices(&mut self, vertices: &VertexArray, transform: Transform, texture: Option<&TextureRef>) {
+        self.draw_with_renderstates(vertices, RenderStates::new(BlendMode::ALPHA, transform, texture, None));
+    }
+
+    fn present(&mut self) {
+        self.display();
+    }
+
+}
+
+#[cfg(test)]
+#[derive(Clone,Debug,PartialEq)]
+pub enum DrawCommand {
+    Fill(Color),
+    Rectangle { bounds: FloatRect, fill: Color, outline: Color, outline_thickness: f32, rotation: f32 },
+    Text { string: String, size: u32, position: (f32, f32), color: Color },
+    // Vertex arrays are summarised by a checksum of their positions and colours, rather than
+    // recording every vertex.
+    Vertices { count: usize, offset: (f32, f32), textured: bool, checksum: u64 },
+    Present,
+}
+
+#[cfg(test)]
+// Records what would have been drawn, so that it can be compared against a known good recording.
+pub struct RecordingRenderer {
+    pub commands: Vec<DrawCommand>,
+}
+
+#[cfg(test)]
+impl RecordingRenderer {
+
+    pub fn new() -> RecordingRenderer {
+        RecordingRenderer {
+            commands: Vec::new(),
+        }
+    }
+
+    // One command per line.
+    pub fn snapshot(&self) -> String {
+        let mut snapshot = String::new();
+
+        for command in self.commands.iter() {
+            snapshot.push_str(&command.to_string());
+            snapshot.push('\n');
+        }
+
+        snapshot
+    }
+
+}
+
+#[cfg(test)]
+impl Renderer for RecordingRenderer {
+
+    fn fill(&mut self, color: &Color) {
+        self.commands.push(DrawCommand::Fill(*color));
+    }
+
+    fn rectangle(&mut self, rect: &RectangleShape) {
+        self.commands.push(DrawCommand::Rectangle {
+            bounds: rect.global_bounds(),
+            fill: rect.fill_color(),
+            outline: rect.outline_color(),
+            outline_thickness: rect.outline_thickness(),
+            rotation: rect.rotation(),
+        });
+    }
+
+    fn text(&mut self, text: &Text) {
+        let position = text.position();
+
+        self.commands.push(DrawCommand::Text {
+            string: text.string(),
+            size: text.character_size(),
+            position: (position.x, position.y),
+            color: text.fill_color(),
+        });
+    }
+
+    fn vertices(&mut self, vertices: &VertexArray, transform: Transform, texture: Option<&TextureRef>) {
+        // FNV-1a over the rounded positions and colours
+        let mut checksum: u64 = 0xcbf29ce484222325;
+
+        for index in 0..vertices.vertex_count() {
+            let vertex = &vertices[index];
+            let values = [
+                (vertex.position.x * 100.0).round() as i64,
+                (vertex.position.y * 100.0).round() as i64,
+                vertex.color.r as i64,
+                vertex.color.g as i64,
+                vertex.color.b as i64,
+                vertex.color.a as i64,
+            ];
+
+            for value in values.iter() {
+                for byte in value.to_le_bytes().iter() {
+                    checksum ^= *byte as u64;
+                    checksum = checksum.wrapping_mul(0x100000001b3);
+                }
+            }
+        }
+
+        let matrix = transform.matrix();
+
+        self.commands.push(DrawCommand::Vertices {
+            count: vertices.vertex_count(),
+            offset: (matrix[12], matrix[13]),
+            textured: texture.is_some(),
+            checksum,
+        });
+    }
+
+    fn present(&mut self) {
+        self.commands.push(DrawCommand::Present);
+    }
+
+}
+
+#[cfg(test)]
+impl fmt::Display for DrawCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DrawCommand::Fill(color) => write!(f, "fill {}", ColorHex(color)),
+            DrawCommand::Rectangle { bounds, fill, outline, outline_thickness, rotation } => write!(
+                f, "rectangle {:.1},{:.1} {:.1}x{:.1} fill {} outline {} {:.1} rotation {:.1}",
+                bounds.left, bounds.top, bounds.width, bounds.height, ColorHex(fill), ColorHex(outline), outline_thickness, rotation
+            ),
+            DrawCommand::Text { ref string, size, position, color } => write!(
+                f, "text {:?} size {} at {:.1},{:.1} colour {}", string, size, position.0, position.1, ColorHex(color)
+            ),
+            DrawCommand::Vertices { count, offset, textured, checksum } => write!(
+                f, "vertices {} at {:.1},{:.1}{} checksum {:016x}", count, offset.0, offset.1, if textured { " textured" } else { "" }, checksum
+            ),
+            DrawCommand::Present => write!(f, "present"),
+        }
+    }
+}
+
+#[cfg(test)]
+struct ColorHex(Color);
+
+#[cfg(test)]
+impl fmt::Display for ColorHex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}{:02x}", self.0.r, self.0.g, self.0.b, self.0.a)
+    }
+}
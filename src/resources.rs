@@ -1,30 +1,92 @@
+use std::cell::{Ref, RefCell};
 use std::collections::HashMap;
+use std::fs;
 use sfml::graphics::Font;
 
+use level::LevelPack;
+use level::tileset::Tileset;
+use save::SaveData;
+use settings::Settings;
+use theme::Theme;
+use typography::Typography;
+
 // Maybe hold a struct for all the resources that need to be loaded at different times. In an
 // Option<T>? so that they can be initialsed at a later date. Perhaps this is what futures are for?
 pub struct Resources {
-    pub menu_res: MenuResources,
+    pub fonts: HashMap<String, Font>, // Keyed by file stem, e.g. "Raleway-BoldItalic"
+    pub typography: Typography,
+    pub themes: Vec<Theme>,
+    pub settings: Settings,
+    pub tileset: Option<Tileset>, // Tiles are drawn procedurally if there isn't one
+    pub packs: Vec<LevelPack>, // The built-in packs, then the player's own
+    pub save: RefCell<SaveData>,
+    theme: RefCell<Theme>, // The selected theme, as seen with the selected colour vision
     pub num: i32,
 }
 
 impl Resources {
+
+    const FONT_DIR: &'static str = "res";
+    const DEFAULT_FONT: &'static str = "Raleway-Regular";
+
     pub fn new() -> Resources {
+        let themes = Theme::load_all();
+
         Resources {
-            menu_res: MenuResources::new(),
+            fonts: Self::load_fonts(),
+            typography: Typography::from_file(Typography::FILE),
+            theme: RefCell::new(themes[0].clone()),
+            themes,
+            settings: Settings::new(),
+            tileset: Tileset::from_file(Tileset::FILE),
+            packs: LevelPack::load_all(),
+            save: RefCell::new(SaveData::from_file(SaveData::FILE)),
             num: 0,
         }
     }
-}
 
-pub struct MenuResources {
-    pub raleway: Font,
-}
+    pub fn theme(&self) -> Ref<Theme> {
+        self.theme.borrow()
+    }
 
-impl MenuResources {
-    fn new() -> MenuResources {
-        MenuResources {
-            raleway: Font::from_file("res/Raleway-Regular.ttf").unwrap(),
+    pub fn next_theme(&self) {
+        let index = (self.settings.theme.get() + 1) % self.themes.len();
+        self.settings.theme.set(index);
+        self.refresh_theme();
+    }
+
+    pub fn next_color_vision(&self) {
+        let vision = self.settings.color_vision.get().next();
+        self.settings.color_vision.set(vision);
+        self.refresh_theme();
+    }
+
+    fn refresh_theme(&self) {
+        let theme = &self.themes[self.settings.theme.get()];
+        *self.theme.borrow_mut() = theme.simulate(self.settings.color_vision.get());
+    }
+
+    // Falls back to the regular weight if the font hasn't been bundled.
+    pub fn font(&self, name: &str) -> &Font {
+        self.fonts.get(name)
+            .or_else(|| self.fonts.get(Self::DEFAULT_FONT))
+            .expect("The default font is missing!")
+    }
+
+    fn load_fonts() -> HashMap<String, Font> {
+        let mut fonts = HashMap::new();
+
+        for entry in fs::read_dir(Self::FONT_DIR).expect("Could not read the resource directory!") {
+            let path = entry.unwrap().path();
+
+            if path.extension().map_or(false, |ext| ext == "ttf") {
+                let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+                let font = Font::from_file(path.to_str().unwrap()).unwrap();
+
+                fonts.insert(name, font);
+            }
         }
+
+        fonts
     }
 }
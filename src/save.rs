@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+
+use challenge::Challenge;
+
+// The player's progress, kept between runs. One line per completed level, keyed by the id of its
+// pack and the level's name, with the best of each result:
+//
+//     # pack  level  rating  stars  moves
+//     main    01     3       2      12
+//
+// Ratings are out of three. The best challenge results for each level follow, best first, with how
+// long they took in frames:
+//
+//     # challenge  mode  pack  level  moves  frames
+//     challenge    both  main  01     13     1840
+//
+// Pack ids and level names are one word, as packs and levels with spaces in their names aren't
+// loaded. The file is rewritten whenever a level is completed.
+pub struct SaveData {
+    records: HashMap<(String, String), LevelRecord>, // By pack id and level name
+    leaderboards: HashMap<(String, String, String), Vec<ChallengeRecord>>, // By challenge key, pack id and level name
+}
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub struct LevelRecord {
+    pub rating: u32,
+    pub stars: u32,
+    pub moves: u32,
+}
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub struct ChallengeRecord {
+    pub moves: u32,
+    pub frames: u32,
+}
+
+impl SaveData {
+
+    pub const FILE: &'static str = "save.txt";
+
+    // Starts each leaderboard line, so can't be used as a pack id
+    pub const CHALLENGE_TAG: &'static str = "challenge";
+
+    const LEADERBOARD_SIZE: usize = 5;
+
+    pub fn new() -> SaveData {
+        SaveData {
+            records: HashMap::new(),
+            leaderboards: HashMap::new(),
+        }
+    }
+
+    // Starts afresh if there is no save file yet.
+    pub fn from_file(path: &str) -> SaveData {
+        let mut save = Self::new();
+
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(_) => return save,
+        };
+
+        for (line_num, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.expect("Could not read save file!");
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            // A bad line is skipped rather than losing the rest of the player's progress
+            if fields[0] == Self::CHALLENGE_TAG {
+                match Self::parse_challenge_line(fields) {
+                    Some((key, record)) => save.leaderboards.entry(key).or_insert_with(Vec::new).push(record),
+                    None => eprintln!("Skipping invalid challenge result on line {} of {}", line_num + 1, path),
+                }
+
+                continue;
+            }
+
+            match Self::parse_line(fields) {
+                Some((key, record)) => { save.records.insert(key, record); },
+                None => eprintln!("Skipping invalid record on line {} of {}", line_num + 1, path),
+            }
+        }
+
+        save
+    }
+
+    fn parse_line(fields: Vec<&str>) -> Option<((String, String), LevelRecord)> {
+        if fields.len() != 5 {
+            return None;
+        }
+
+        Some(((fields[0].to_string(), fields[1].to_string()), LevelRecord {
+            rating: fields[2].parse().ok()?,
+            stars: fields[3].parse().ok()?,
+            moves: fields[4].parse().ok()?,
+        }))
+    }
+
+    fn parse_challenge_line(fields: Vec<&str>) -> Option<((String, String, String), ChallengeRecord)> {
+        if fields.len() != 6 || Challenge::from_key(fields[1]).is_none() {
+            return None;
+        }
+
+        Some(((fields[1].to_string(), fields[2].to_string(), fields[3].to_string()), ChallengeRecord {
+            moves: fields[4].parse().ok()?,
+            frames: fields[5].parse().ok()?,
+        }))
+    }
+
+    pub fn write(&self, path: &str) -> io::Result<()> {
+        let mut levels: Vec<&(String, String)> = self.records.keys().collect();
+        levels.sort();
+
+        let mut contents = String::from("# pack  level  rating  stars  moves\n");
+
+        for level in levels {
+            let record = &self.records[level];
+            contents.push_str(&format!("{}  {}  {}  {}  {}\n", level.0, level.1, record.rating, record.stars, record.moves));
+        }
+
+        let mut keys: Vec<&(String, String, String)> = self.leaderboards.keys().collect();
+        keys.sort();
+
+        if !keys.is_empty() {
+            contents.push_str("\n# challenge  mode  pack  level  moves  frames\n");
+        }
+
+        for key in keys {
+            for record in self.leaderboards[key].iter() {
+                contents.push_str(&format!("{}  {}  {}  {}  {}  {}\n", Self::CHALLENGE_TAG, key.0, key.1, key.2, record.moves, record.frames));
+            }
+        }
+
+        File::create(path).and_then(|mut file| file.write_all(contents.as_bytes()))
+    }
+
+    pub fn record(&self, pack_id: &str, level_name: &str) -> Option<&LevelRecord> {
+        self.records.get(&(pack_id.to_string(), level_name.to_string()))
+    }
+
+    // Keeps the best rating, the most stars and the fewest moves, which may be from different
+    // attempts.
+    pub fn complete_level(&mut self, pack_id: &str, level_name: &str, result: LevelRecord) {
+        let best = self.records.entry((pack_id.to_string(), level_name.to_string())).or_insert(result);
+
+        best.rating = best.rating.max(result.rating);
+        best.stars = best.stars.max(result.stars);
+        best.moves = best.moves.min(result.moves);
+    }
+
+    // The best results for the level under the challenge, best first
+    pub fn leaderboard(&self, challenge: Challenge, pack_id: &str, level_name: &str) -> &[ChallengeRecord] {
+        match self.leaderboards.get(&Self::leaderboard_key(challenge, pack_id, level_name)) {
+            Some(records) => records,
+            None => &[],
+        }
+    }
+
+    // Adds the result to the level's leaderboard, returning where it placed (from zero) if it was
+    // good enough to stay on it. Timed challenges are ranked by time first, others by moves.
+    pub fn add_challenge_result(&mut self, challenge: Challenge, pack_id: &str, level_name: &str, result: ChallengeRecord) -> Option<usize> {
+        let records = self.leaderboards.entry(Self::leaderboard_key(challenge, pack_id, level_name)).or_insert_with(Vec::new);
+
+        let rank_key = |record: &ChallengeRecord| match challenge {
+            Challenge::Time => (record.frames, record.moves),
+            _ => (record.moves, record.frames),
+        };
+
+        // After any equal results, so that earlier ones keep their place
+        let place = records.iter().position(|record| rank_key(record) > rank_key(&result)).unwrap_or(records.len());
+
+        records.insert(place, result);
+        records.truncate(Self::LEADERBOARD_SIZE);
+
+        if place < Self::LEADERBOARD_SIZE { Some(place) } else { None }
+    }
+
+    fn leaderboard_key(challenge: Challenge, pack_id: &str, level_name: &str) -> (String, String, String) {
+        (challenge.key().to_string(), pack_id.to_string(), level_name.to_string())
+    }
+
+}
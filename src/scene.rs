@@ -1,32 +1,44 @@
-use sfml::graphics::RenderWindow;
 use sfml::window::Event;
 
 use resources::Resources;
 use ::SplashScene;
-use menu::MainMenu;
-use level::Level;
+use menu::{LevelSelect, MainMenu, PackSelect, SettingsMenu};
+use level::{Editor, Level, LevelData};
+use renderer::Renderer;
 
 // TODO: Add transitions
 
 pub trait Sceneable {
     fn update(&mut self, resources: &Resources) -> SceneAction;
-    fn draw(&self, window: &mut RenderWindow);
+    fn draw(&self, renderer: &mut Renderer, resources: &Resources);
     fn handle_event(&mut self, event: Event, resources: &Resources) -> SceneAction;
 }
 
 #[derive(Clone)]
 pub enum Scene {
     Splash,
-    Level(usize), // Level ID
+    Level(usize, usize), // Pack ID and level ID
     MainMenu,
+    Settings,
+    PackSelect,
+    LevelSelect(usize), // Pack ID
+    Editor,
+    EditLevel(usize, usize), // Pack ID and level ID, of one of the player's own packs
+    TestLevel(LevelData), // Played from the editor
 }
 
 impl Scene {
     fn to_obj<'a>(self, resources: &'a Resources) -> Box<Sceneable + 'a> {
         match self {
             Scene::Splash => SplashScene::new(resources),
-            Scene::Level(id) => Level::new(id),
+            Scene::Level(pack_id, level_id) => Level::new(resources, pack_id, level_id),
             Scene::MainMenu => MainMenu::new(resources),
+            Scene::Settings => SettingsMenu::new(resources),
+            Scene::PackSelect => PackSelect::new(resources),
+            Scene::LevelSelect(pack_id) => LevelSelect::new(resources, pack_id),
+            Scene::Editor => Editor::new(resources),
+            Scene::EditLevel(pack_id, level_id) => Editor::open(resources, pack_id, level_id),
+            Scene::TestLevel(data) => Level::test(resources, data),
         }
     }
 }
@@ -90,12 +102,12 @@ impl<'a> SceneManager<'a> {
         self.handle_scene_action(action, resources);
     }
 
-    pub fn draw(&mut self, window: &mut RenderWindow) {
+    pub fn draw(&mut self, renderer: &mut Renderer, resources: &Resources) {
         for scene in self.scenes.iter() {
-            scene.draw(window);
+            scene.draw(renderer, resources);
         }
 
-        window.display();
+        renderer.present();
     }
 
     pub fn handle_event(&mut self, event: Event, resources: &'a Resources) {
@@ -0,0 +1,31 @@
+use std::cell::Cell;
+
+use accessibility::ColorVision;
+
+use challenge::Challenge;
+
+// Options which can be changed at runtime from the settings menu. Scenes only ever see
+// `&Resources`, so each option is held in a Cell.
+pub struct Settings {
+    pub theme: Cell<usize>, // Index into Resources::themes
+    pub tile_shapes: Cell<bool>, // Draw a distinct pattern on each kind of tile, not just a colour
+    pub color_vision: Cell<ColorVision>,
+    pub teleporter_links: Cell<bool>, // Outline linked teleporters when hovering over one
+    pub auto_restart: Cell<bool>, // Restart straight away after falling down a hole, rather than asking
+    pub challenge: Cell<Challenge>, // For levels started from now on
+    pub show_hud: Cell<bool>, // The level, moves, time and controls over the board
+}
+
+impl Settings {
+    pub fn new() -> Settings {
+        Settings {
+            theme: Cell::new(0),
+            tile_shapes: Cell::new(false),
+            color_vision: Cell::new(ColorVision::Normal),
+            teleporter_links: Cell::new(true),
+            auto_restart: Cell::new(false),
+            challenge: Cell::new(Challenge::Off),
+            show_hud: Cell::new(true),
+        }
+    }
+}
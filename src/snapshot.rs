@@ -0,0 +1,114 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use resources::Resources;
+use scene::Sceneable;
+use renderer::RecordingRenderer;
+use menu::{LevelSelect, MainMenu, PackSelect};
+use save::SaveData;
+use level::Level;
+use ::SplashScene;
+
+// Golden-file tests for how scenes look. Each scene is drawn into a RecordingRenderer and the
+// recording is compared with the one saved in snapshots/:
+//
+//     cargo test -- --ignored                       Fails if any recording differs from its snapshot, or has none
+//     BLESS_SNAPSHOTS=1 cargo test -- --ignored     Writes the snapshots from the new recordings
+//
+// Nothing is drawn to a window, but text is still measured with SFML's fonts, which need an
+// OpenGL context, so the test is ignored unless asked for. On a machine without a display, such as
+// CI, run it under a virtual one with Xvfb:
+//
+//     xvfb-run cargo test -- --ignored
+//
+// Only the built-in level packs are recorded, so that the player's own don't change the results.
+const DIR: &'static str = "snapshots";
+
+#[test]
+#[ignore]
+fn scenes_match_snapshots() {
+    let bless = env::var_os("BLESS_SNAPSHOTS").is_some();
+    assert!(run(bless), "Some scenes don't match their snapshots, see above");
+}
+
+fn run(bless: bool) -> bool {
+    let mut resources = Resources::new();
+
+    // Don't let the player's own packs or progress show up in the recordings
+    resources.packs.retain(|pack| !pack.user);
+    *resources.save.borrow_mut() = SaveData::new();
+
+    let mut passed = true;
+
+    passed &= check("splash", &*SplashScene::new(&resources), &resources, bless);
+
+    let mut menu = MainMenu::new(&resources);
+    // Let the menu finish fading in
+    for _frame in 0..61 {
+        menu.update(&resources);
+    }
+    passed &= check("main_menu", &*menu, &resources, bless);
+
+    let mut pack_select = PackSelect::new(&resources);
+    pack_select.update(&resources);
+    passed &= check("pack_select", &*pack_select, &resources, bless);
+
+    for (pack_id, pack) in resources.packs.iter().enumerate() {
+        let mut level_select = LevelSelect::new(&resources, pack_id);
+        level_select.update(&resources);
+        passed &= check(&format!("level_select_{}", pack.id), &*level_select, &resources, bless);
+
+        for level_id in 0..pack.levels.len() {
+            let level = Level::new(&resources, pack_id, level_id);
+            passed &= check(&format!("level_{}_{}", pack.id, level_id), &*level, &resources, bless);
+        }
+    }
+
+    passed
+}
+
+fn check(name: &str, scene: &Sceneable, resources: &Resources, bless: bool) -> bool {
+    let mut renderer = RecordingRenderer::new();
+    scene.draw(&mut renderer, resources);
+
+    let recording = renderer.snapshot();
+    let path = Path::new(DIR).join(format!("{}.snap", name));
+
+    let mut expected = String::new();
+    let exists = File::open(&path).and_then(|mut file| file.read_to_string(&mut expected)).is_ok();
+
+    if bless {
+        fs::create_dir_all(DIR).expect("Could not create the snapshot directory!");
+        File::create(&path).and_then(|mut file| file.write_all(recording.as_bytes()))
+            .expect("Could not write snapshot!");
+
+        println!("{}: written", name);
+        return true;
+    }
+
+    if !exists {
+        println!("{}: FAILED, no snapshot at {} (run with BLESS_SNAPSHOTS=1 to write it)", name, path.display());
+        return false;
+    }
+
+    if recording == expected {
+        println!("{}: ok", name);
+        return true;
+    }
+
+    println!("{}: FAILED", name);
+
+    for (line_num, (got, want)) in recording.lines().zip(expected.lines()).enumerate() {
+        if got != want {
+            println!("  line {}:\n    expected: {}\n    got:      {}", line_num + 1, want, got);
+        }
+    }
+
+    if recording.lines().count() != expected.lines().count() {
+        println!("  expected {} commands, got {}", expected.lines().count(), recording.lines().count());
+    }
+
+    false
+}
@@ -0,0 +1,328 @@
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use sfml::graphics::Color;
+
+use accessibility::ColorVision;
+
+#[derive(Clone,Debug)]
+pub struct Theme {
+    pub name: String,
+
+    pub background: Color,
+    pub text: Color,
+    pub muted_text: Color,
+    pub accent: Color,
+
+    pub level_background: Color,
+    pub board: Color,
+    pub grid_line: Color,
+
+    pub empty: Color,
+    pub wall: Color,
+    pub target: Color,
+    pub snow: Color,
+    pub hole: Color,
+    pub block: Color,
+    pub door: Color, // Doors and the switches which work them
+    pub keys: [Color; 4], // Keys and locks, in the order red, green, yellow, purple
+    pub star: Color,
+    pub teleporters: [Color; 4], // One per teleporter index, repeating after the fourth pair
+    pub oneway: Color,
+    pub deflector: Color,
+    pub conveyor: Color,
+    pub player: Color,
+}
+
+impl Theme {
+
+    pub const DIR: &'static str = "res/themes";
+
+    const COLOR_KEYS: [&'static str; 27] = [
+        "background", "text", "muted_text", "accent",
+        "level_background", "board", "grid_line",
+        "empty", "wall", "target", "snow", "hole", "teleporter", "teleporter_2", "teleporter_3", "teleporter_4",
+        "oneway", "deflector", "conveyor", "player", "block", "door",
+        "key_red", "key_green", "key_yellow", "key_purple", "star",
+    ];
+
+    pub fn light() -> Theme {
+        Theme {
+            name: "Light".to_string(),
+
+            background: Color { r: 240, g: 240, b: 240, a: 255 },
+            text: Color { r: 50, g: 50, b: 50, a: 255 },
+            muted_text: Color { r: 100, g: 100, b: 100, a: 255 },
+            accent: Color { r: 195, g: 77, b: 88, a: 255 },
+
+            level_background: Color { r: 242, g: 242, b: 242, a: 255 },
+            board: Color::WHITE,
+            grid_line: Color { r: 128, g: 128, b: 128, a: 64 },
+
+            empty: Color::WHITE,
+            wall: Color { r: 84, g: 97, b: 112, a: 255 },
+            target: Color { r: 198, g: 243, b: 100, a: 255 },
+            snow: Color { r: 214, g: 226, b: 236, a: 255 },
+            hole: Color { r: 40, g: 44, b: 52, a: 255 },
+            teleporters: [
+                Color { r: 255, g: 107, b: 107, a: 255 },
+                Color { r: 255, g: 170, b: 60, a: 255 },
+                Color { r: 150, g: 110, b: 230, a: 255 },
+                Color { r: 60, g: 140, b: 230, a: 255 },
+            ],
+            oneway: Color { r: 195, g: 77, b: 88, a: 255 },
+            deflector: Color { r: 70, g: 100, b: 150, a: 255 },
+            conveyor: Color { r: 150, g: 160, b: 175, a: 255 },
+            player: Color { r: 78, g: 204, b: 195, a: 255 },
+            block: Color { r: 176, g: 128, b: 82, a: 255 },
+            door: Color { r: 109, g: 93, b: 180, a: 255 },
+            keys: [
+                Color { r: 220, g: 60, b: 60, a: 255 },
+                Color { r: 60, g: 170, b: 90, a: 255 },
+                Color { r: 230, g: 185, b: 40, a: 255 },
+                Color { r: 160, g: 80, b: 200, a: 255 },
+            ],
+            star: Color { r: 245, g: 190, b: 30, a: 255 },
+        }
+    }
+
+    pub fn dark() -> Theme {
+        Theme {
+            name: "Dark".to_string(),
+
+            background: Color { r: 28, g: 31, b: 38, a: 255 },
+            text: Color { r: 220, g: 220, b: 220, a: 255 },
+            muted_text: Color { r: 150, g: 150, b: 150, a: 255 },
+            accent: Color { r: 224, g: 108, b: 117, a: 255 },
+
+            level_background: Color { r: 22, g: 24, b: 29, a: 255 },
+            board: Color { r: 37, g: 41, b: 50, a: 255 },
+            grid_line: Color { r: 200, g: 200, b: 200, a: 40 },
+
+            empty: Color { r: 37, g: 41, b: 50, a: 255 },
+            wall: Color { r: 92, g: 103, b: 115, a: 255 },
+            target: Color { r: 152, g: 195, b: 121, a: 255 },
+            snow: Color { r: 150, g: 160, b: 175, a: 255 },
+            hole: Color { r: 10, g: 11, b: 14, a: 255 },
+            teleporters: [
+                Color { r: 224, g: 108, b: 117, a: 255 },
+                Color { r: 229, g: 192, b: 123, a: 255 },
+                Color { r: 198, g: 120, b: 221, a: 255 },
+                Color { r: 97, g: 175, b: 239, a: 255 },
+            ],
+            oneway: Color { r: 190, g: 80, b: 70, a: 255 },
+            deflector: Color { r: 130, g: 160, b: 210, a: 255 },
+            conveyor: Color { r: 85, g: 95, b: 115, a: 255 },
+            player: Color { r: 86, g: 182, b: 194, a: 255 },
+            block: Color { r: 181, g: 137, b: 96, a: 255 },
+            door: Color { r: 150, g: 130, b: 220, a: 255 },
+            keys: [
+                Color { r: 235, g: 95, b: 95, a: 255 },
+                Color { r: 110, g: 200, b: 120, a: 255 },
+                Color { r: 240, g: 205, b: 90, a: 255 },
+                Color { r: 190, g: 120, b: 230, a: 255 },
+            ],
+            star: Color { r: 250, g: 210, b: 80, a: 255 },
+        }
+    }
+
+    // Pure colours on black, for players who find the other themes hard to read.
+    pub fn high_contrast() -> Theme {
+        Theme {
+            name: "High Contrast".to_string(),
+
+            background: Color::BLACK,
+            text: Color::WHITE,
+            muted_text: Color { r: 200, g: 200, b: 200, a: 255 },
+            accent: Color::YELLOW,
+
+            level_background: Color::BLACK,
+            board: Color::BLACK,
+            grid_line: Color { r: 255, g: 255, b: 255, a: 128 },
+
+            empty: Color::BLACK,
+            wall: Color::WHITE,
+            target: Color::GREEN,
+            snow: Color { r: 160, g: 160, b: 160, a: 255 },
+            hole: Color { r: 90, g: 0, b: 0, a: 255 },
+            teleporters: [
+                Color::MAGENTA,
+                Color { r: 255, g: 128, b: 0, a: 255 },
+                Color { r: 128, g: 128, b: 255, a: 255 },
+                Color::RED,
+            ],
+            oneway: Color::YELLOW,
+            deflector: Color { r: 255, g: 128, b: 0, a: 255 },
+            conveyor: Color { r: 128, g: 128, b: 128, a: 255 },
+            player: Color::CYAN,
+            block: Color { r: 0, g: 160, b: 255, a: 255 },
+            door: Color { r: 0, g: 255, b: 160, a: 255 },
+            keys: [
+                Color::RED,
+                Color::GREEN,
+                Color::YELLOW,
+                Color::MAGENTA,
+            ],
+            star: Color::YELLOW,
+        }
+    }
+
+    // The built-in themes followed by any themes found in res/themes.
+    pub fn load_all() -> Vec<Theme> {
+        let mut themes = vec![Self::light(), Self::dark(), Self::high_contrast()];
+
+        if let Ok(entries) = fs::read_dir(Self::DIR) {
+            let mut paths: Vec<_> = entries.map(|entry| entry.unwrap().path())
+                .filter(|path| path.extension().map_or(false, |ext| ext == "theme"))
+                .collect();
+
+            paths.sort();
+
+            for path in paths {
+                themes.push(Self::from_file(path.to_str().unwrap()));
+            }
+        }
+
+        themes
+    }
+
+    // Colours missing from the file are taken from the light theme. The name defaults to the
+    // file name.
+    pub fn from_file(path: &str) -> Theme {
+        let file = File::open(path).expect("Could not open theme file!");
+
+        let mut theme = Self::light();
+        theme.name = Path::new(path).file_stem().unwrap().to_string_lossy().into_owned();
+
+        for (line_num, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.expect("Could not read theme file!");
+            let line = line.trim();
+
+            // Comments take a whole line, as colours also start with a '#'.
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.splitn(2, char::is_whitespace);
+            let key = fields.next().unwrap();
+            let value = fields.next().unwrap_or("").trim();
+
+            if key == "name" {
+                theme.name = value.to_string();
+                continue;
+            }
+
+            let color = theme.color_mut(key)
+                .unwrap_or_else(|| panic!("Unknown colour '{}' on line {} of {}", key, line_num + 1, path));
+
+            *color = parse_hex_color(value)
+                .unwrap_or_else(|| panic!("Invalid colour on line {} of {}", line_num + 1, path));
+        }
+
+        theme
+    }
+
+    pub fn color(&self, key: &str) -> Option<Color> {
+        match key {
+            "background" => Some(self.background),
+            "text" => Some(self.text),
+            "muted_text" => Some(self.muted_text),
+            "accent" => Some(self.accent),
+            "level_background" => Some(self.level_background),
+            "board" => Some(self.board),
+            "grid_line" => Some(self.grid_line),
+            "empty" => Some(self.empty),
+            "wall" => Some(self.wall),
+            "target" => Some(self.target),
+            "snow" => Some(self.snow),
+            "hole" => Some(self.hole),
+            "teleporter" => Some(self.teleporters[0]),
+            "teleporter_2" => Some(self.teleporters[1]),
+            "teleporter_3" => Some(self.teleporters[2]),
+            "teleporter_4" => Some(self.teleporters[3]),
+            "oneway" => Some(self.oneway),
+            "deflector" => Some(self.deflector),
+            "conveyor" => Some(self.conveyor),
+            "player" => Some(self.player),
+            "block" => Some(self.block),
+            "door" => Some(self.door),
+            "key_red" => Some(self.keys[0]),
+            "key_green" => Some(self.keys[1]),
+            "key_yellow" => Some(self.keys[2]),
+            "key_purple" => Some(self.keys[3]),
+            "star" => Some(self.star),
+            _ => None,
+        }
+    }
+
+    pub fn teleporter(&self, index: u32) -> Color {
+        self.teleporters[index as usize % self.teleporters.len()]
+    }
+
+    pub fn key(&self, index: usize) -> Color {
+        self.keys[index]
+    }
+
+    // A copy of this theme as it would appear with the given colour vision.
+    pub fn simulate(&self, vision: ColorVision) -> Theme {
+        let mut theme = self.clone();
+
+        for key in Self::COLOR_KEYS.iter() {
+            let color = theme.color_mut(key).unwrap();
+            *color = vision.simulate(*color);
+        }
+
+        theme
+    }
+
+    fn color_mut(&mut self, key: &str) -> Option<&mut Color> {
+        match key {
+            "background" => Some(&mut self.background),
+            "text" => Some(&mut self.text),
+            "muted_text" => Some(&mut self.muted_text),
+            "accent" => Some(&mut self.accent),
+            "level_background" => Some(&mut self.level_background),
+            "board" => Some(&mut self.board),
+            "grid_line" => Some(&mut self.grid_line),
+            "empty" => Some(&mut self.empty),
+            "wall" => Some(&mut self.wall),
+            "target" => Some(&mut self.target),
+            "snow" => Some(&mut self.snow),
+            "hole" => Some(&mut self.hole),
+            "teleporter" => Some(&mut self.teleporters[0]),
+            "teleporter_2" => Some(&mut self.teleporters[1]),
+            "teleporter_3" => Some(&mut self.teleporters[2]),
+            "teleporter_4" => Some(&mut self.teleporters[3]),
+            "oneway" => Some(&mut self.oneway),
+            "deflector" => Some(&mut self.deflector),
+            "conveyor" => Some(&mut self.conveyor),
+            "player" => Some(&mut self.player),
+            "block" => Some(&mut self.block),
+            "door" => Some(&mut self.door),
+            "key_red" => Some(&mut self.keys[0]),
+            "key_green" => Some(&mut self.keys[1]),
+            "key_yellow" => Some(&mut self.keys[2]),
+            "key_purple" => Some(&mut self.keys[3]),
+            "star" => Some(&mut self.star),
+            _ => None,
+        }
+    }
+
+}
+
+// Accepts #rrggbb or #rrggbbaa.
+pub fn parse_hex_color(hex: &str) -> Option<Color> {
+    if !hex.starts_with('#') || (hex.len() != 7 && hex.len() != 9) {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+
+    Some(Color {
+        r: channel(1)?,
+        g: channel(3)?,
+        b: channel(5)?,
+        a: if hex.len() == 9 { channel(7)? } else { 255 },
+    })
+}
@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use resources::Resources;
+use renderer::Renderer;
+use theme::Theme;
+
+use sfml::system::Vector2f;
+use sfml::graphics::{Color, FloatRect, Font, Text, Transformable};
+
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+pub enum TextStyle {
+    Title,
+    MenuItem,
+    Body,
+    Caption,
+    Hud,
+}
+
+#[derive(Clone,Debug)]
+pub struct StyleSpec {
+    pub font: String, // File stem of the font in res/, e.g. "Raleway-Light"
+    pub size: u32,
+    pub color: String, // Key into the current Theme, e.g. "text" or "accent"
+    pub letter_spacing: f32, // Extra pixels inserted between characters
+}
+
+pub struct Typography {
+    styles: HashMap<TextStyle, StyleSpec>,
+}
+
+impl TextStyle {
+
+    const ALL: [TextStyle; 5] = [TextStyle::Title, TextStyle::MenuItem, TextStyle::Body, TextStyle::Caption, TextStyle::Hud];
+
+    fn from_name(name: &str) -> Option<TextStyle> {
+        match name {
+            "title" => Some(TextStyle::Title),
+            "menu_item" => Some(TextStyle::MenuItem),
+            "body" => Some(TextStyle::Body),
+            "caption" => Some(TextStyle::Caption),
+            "hud" => Some(TextStyle::Hud),
+            _ => None,
+        }
+    }
+
+    fn default_spec(&self) -> StyleSpec {
+        let (font, size, color, letter_spacing) = match *self {
+            TextStyle::Title    => ("Raleway-Light", 50, "text", 2.0),
+            TextStyle::MenuItem => ("Raleway-Regular", 28, "text", 0.0),
+            TextStyle::Body     => ("Raleway-Regular", 22, "text", 0.0),
+            TextStyle::Caption  => ("Raleway-Italic", 16, "muted_text", 0.0),
+            TextStyle::Hud      => ("Raleway-SemiBold", 20, "text", 1.0),
+        };
+
+        StyleSpec {
+            font: font.to_string(),
+            size,
+            color: color.to_string(),
+            letter_spacing,
+        }
+    }
+
+}
+
+impl Typography {
+
+    pub const FILE: &'static str = "res/typography.theme";
+
+    pub fn new() -> Typography {
+        let mut styles = HashMap::new();
+
+        for style in TextStyle::ALL.iter() {
+            styles.insert(*style, style.default_spec());
+        }
+
+        Typography {
+            styles,
+        }
+    }
+
+    // Styles missing from the file keep their defaults. If the file doesn't exist at all, every
+    // style uses its default.
+    pub fn from_file(path: &str) -> Typography {
+        let mut typography = Self::new();
+
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(_) => return typography,
+        };
+
+        for (line_num, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.expect("Could not read typography file!");
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (style, spec) = Self::parse_line(line)
+                .unwrap_or_else(|| panic!("Invalid style on line {} of {}", line_num + 1, path));
+
+            typography.styles.insert(style, spec);
+        }
+
+        typography
+    }
+
+    // Format: <style> <font> <size> <theme colour> <letter spacing>
+    fn parse_line(line: &str) -> Option<(TextStyle, StyleSpec)> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        if fields.len() != 5 {
+            return None;
+        }
+
+        let style = TextStyle::from_name(fields[0])?;
+
+        Some((style, StyleSpec {
+            font: fields[1].to_string(),
+            size: fields[2].parse().ok()?,
+            color: fields[3].to_string(),
+            letter_spacing: fields[4].parse().ok()?,
+        }))
+    }
+
+    pub fn style(&self, style: TextStyle) -> &StyleSpec {
+        &self.styles[&style]
+    }
+
+    pub fn color(&self, style: TextStyle, theme: &Theme) -> Color {
+        theme.color(&self.style(style).color).unwrap_or(theme.text)
+    }
+
+}
+
+// A Text which is built from a TextStyle. SFML can't space letters itself, so when a style has
+// letter spacing each character is laid out as its own Text.
+#[derive(Clone)]
+pub struct Label<'a> {
+    text: Text<'a>,
+    glyphs: Vec<Text<'a>>,
+    font: &'a Font,
+    style: TextStyle,
+    letter_spacing: f32,
+}
+
+impl<'a> Label<'a> {
+
+    pub fn new(string: &str, style: TextStyle, res: &'a Resources) -> Label<'a> {
+        let spec = res.typography.style(style);
+        let font = res.font(&spec.font);
+
+        let mut text = Text::new(string, font, spec.size);
+        text.set_fill_color(&res.typography.color(style, &res.theme()));
+
+        let mut label = Label {
+            text,
+            glyphs: Vec::new(),
+            font,
+            style,
+            letter_spacing: spec.letter_spacing,
+        };
+
+        label.layout();
+
+        label
+    }
+
+    pub fn set_string(&mut self, string: &str) {
+        self.text.set_string(string);
+        self.layout();
+    }
+
+    // Breaks lines between words, so that none are wider than `width` unless a single word is.
+    pub fn set_wrapped_string(&mut self, string: &str, width: f32) {
+        let mut lines = Vec::new();
+
+        for paragraph in string.split('\n') {
+            let mut line = String::new();
+
+            for word in paragraph.split_whitespace() {
+                let longer = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+                self.set_string(&longer);
+
+                if self.local_bounds().width > width && !line.is_empty() {
+                    lines.push(line);
+                    line = word.to_string();
+                } else {
+                    line = longer;
+                }
+            }
+
+            lines.push(line);
+        }
+
+        self.set_string(&lines.join("\n"));
+    }
+
+    pub fn set_position<P: Into<Vector2f>>(&mut self, position: P) {
+        self.text.set_position(position);
+        self.layout();
+    }
+
+    pub fn set_fill_color(&mut self, color: &Color) {
+        self.text.set_fill_color(color);
+
+        for glyph in self.glyphs.iter_mut() {
+            glyph.set_fill_color(color);
+        }
+    }
+
+    pub fn fill_color(&self) -> Color {
+        self.text.fill_color()
+    }
+
+    // Resets the colour to the one given by the style in the current theme, keeping the alpha.
+    pub fn refresh_color(&mut self, res: &Resources) {
+        let mut color = res.typography.color(self.style, &res.theme());
+        color.a = self.fill_color().a;
+        self.set_fill_color(&color);
+    }
+
+    // Letter spacing widens each line by the gaps between its characters, so the widest line may
+    // not be the one which is widest without it.
+    pub fn local_bounds(&self) -> FloatRect {
+        let mut bounds = self.text.local_bounds();
+
+        if self.letter_spacing == 0.0 {
+            return bounds;
+        }
+
+        let string = self.text.string();
+        let mut widest_natural: f32 = 0.0;
+        let mut widest_spaced: f32 = 0.0;
+        let mut line_start = 0;
+
+        for (index, c) in string.chars().chain(Some('\n')).enumerate() {
+            if c != '\n' {
+                continue;
+            }
+
+            let chars = index - line_start;
+            let natural = self.text.find_character_pos(index).x - self.text.find_character_pos(line_start).x;
+            let spaced = natural + self.letter_spacing * chars.saturating_sub(1) as f32;
+
+            widest_natural = widest_natural.max(natural);
+            widest_spaced = widest_spaced.max(spaced);
+            line_start = index + 1;
+        }
+
+        bounds.width += widest_spaced - widest_natural;
+
+        bounds
+    }
+
+    pub fn draw(&self, renderer: &mut Renderer) {
+        if self.glyphs.is_empty() {
+            renderer.text(&self.text);
+            return;
+        }
+
+        for glyph in self.glyphs.iter() {
+            renderer.text(glyph);
+        }
+    }
+
+    fn layout(&mut self) {
+        self.glyphs.clear();
+
+        if self.letter_spacing == 0.0 {
+            return;
+        }
+
+        let string = self.text.string();
+        let mut column = 0; // Within the line, as the spacing starts again on each one
+
+        for (index, c) in string.chars().enumerate() {
+            if c == '\n' {
+                column = 0;
+                continue;
+            }
+
+            let char_pos = self.text.find_character_pos(index);
+
+            let mut glyph = Text::new(&c.to_string(), self.font, self.text.character_size());
+            glyph.set_fill_color(&self.text.fill_color());
+            glyph.set_position((char_pos.x + self.letter_spacing * column as f32, char_pos.y));
+
+            self.glyphs.push(glyph);
+            column += 1;
+        }
+    }
+
+}